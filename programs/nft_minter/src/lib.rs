@@ -1,31 +1,210 @@
 use anchor_lang::prelude::*;
-use anchor_lang::solana_program::{program::invoke, program::invoke_signed, system_instruction};
-use anchor_spl::token::{Token, Mint};
+use anchor_lang::solana_program::{program::invoke, program::invoke_signed, program_option::COption, program_pack::Pack, system_instruction};
+use anchor_lang::solana_program::{
+    ed25519_program,
+    keccak,
+    sysvar::instructions::{self as sysvar_instructions, load_current_index_checked, load_instruction_at_checked},
+};
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::token::spl_token::instruction::AuthorityType;
 use anchor_spl::associated_token::AssociatedToken;
 use mpl_token_metadata::{
     ID as TOKEN_METADATA_PROGRAM_ID,
-    accounts::{Metadata, MasterEdition},
-    types::{Creator, DataV2, Collection},
+    accounts::{MasterEdition, Metadata},
+    types::{Creator, DataV2, Collection, CollectionDetails, MintNewEditionFromMasterEditionViaTokenArgs},
+    accounts::EditionMarker,
+    EDITION_MARKER_BIT_SIZE,
+    MAX_NAME_LENGTH,
+    MAX_SYMBOL_LENGTH,
+    MAX_URI_LENGTH,
 };
 
 declare_id!("C4FiFWofsjxRGXrcF5i1RnxPHc7QDcSf9XzhFgLQyioh");
 
+/// Leaves room in the 32-byte Metaplex name for the " #<edition>" suffix
+const MAX_BASE_NAME_LEN: usize = 24;
+/// Leaves room in the Metaplex URI for the "<edition>.json" suffix of revealed editions
+const MAX_REVEAL_BASE_URI_LEN: usize = MAX_URI_LENGTH - 25;
+
+/// Accounts per airdrop recipient: recipient, edition mint, token account, metadata, edition
+const AIRDROP_ACCOUNTS_PER_RECIPIENT: usize = 5;
+/// Keeps an airdrop within transaction size and compute limits
+const MAX_AIRDROP_BATCH: usize = 5;
+
+/// Accounts per batch-minted edition: edition mint, token account, metadata, edition
+const MINT_BATCH_ACCOUNTS_PER_EDITION: usize = 4;
+/// Keeps a batch within transaction size and compute limits
+const MAX_MINT_BATCH: u8 = 5;
+
+/// Accounts per snapshotted edition: holder token account, edition metadata
+const SNAPSHOT_ACCOUNTS_PER_EDITION: usize = 2;
+
+/// Accounts per minter read by `get_minter_records`: minter wallet, minter record
+const MINTER_RECORD_READ_ACCOUNTS: usize = 2;
+/// Keeps the summaries within the 1024-byte return data limit
+const MAX_MINTER_RECORDS_READ: usize = 15;
+
+/// Reason bits returned by `eligibility`
+const ELIGIBILITY_PAUSED: u32 = 1 << 0;
+const ELIGIBILITY_SOLD_OUT: u32 = 1 << 1;
+const ELIGIBILITY_NOT_STARTED: u32 = 1 << 2;
+const ELIGIBILITY_ENDED: u32 = 1 << 3;
+const ELIGIBILITY_NOT_ALLOWLISTED: u32 = 1 << 4;
+const ELIGIBILITY_WALLET_LIMIT: u32 = 1 << 5;
+/// The wallet used up today's `max_mints_per_day` and must wait for the next UTC day
+const ELIGIBILITY_COOLDOWN: u32 = 1 << 6;
+
+/// Rarity tiers that can override the default royalty
+const MAX_RARITY_TIERS: usize = 4;
+
+/// Bulk discount tiers for batch mints
+const MAX_BULK_TIERS: usize = 4;
+
+/// Explicit mint counts announced with a `Milestone` event
+const MAX_MILESTONES: usize = 8;
+
+/// Referral codes are used directly as a PDA seed
+const MAX_REFERRAL_CODE_LEN: usize = 32;
+
+/// Size of a config in the first release layout: discriminator, authority, master mint,
+/// both prices, total minted and payment vault
+const FIRST_RELEASE_CONFIG_LEN: usize = 8 + 32 + 32 + 8 + 8 + 8 + 32;
+/// Edition metadata the first release hardcoded, written into migrated configs
+const FIRST_RELEASE_BASE_NAME: &str = "AMMo Founder";
+const FIRST_RELEASE_SYMBOL: &str = "FAMMo";
+const FIRST_RELEASE_URI: &str =
+    "https://plum-imperial-swordfish-193.mypinata.cloud/ipfs/bafkreiddegzxdo2h3sliwjfpp22f46mfwb7frb3aibdqtln74uiiv3wkmy";
+const FIRST_RELEASE_ROYALTY_BPS: u16 = 500;
+
+/// Pyth receiver program that owns PriceUpdateV2 accounts
+const PYTH_RECEIVER_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+/// Anchor discriminator of Pyth's PriceUpdateV2 account
+const PRICE_UPDATE_V2_DISCRIMINATOR: [u8; 8] = [34, 241, 35, 99, 157, 126, 244, 205];
+
 #[program]
 pub mod nft_minter {
     use super::*;
 
-    /// Initialize the minting program with the master edition
-    pub fn initialize(ctx: Context<Initialize>, master_mint: Pubkey) -> Result<()> {
+    /// Initialize the minting program with the master edition, a supply cap (0 is unlimited)
+    /// and an optional SPL payment mint (the default pubkey disables token payments),
+    /// along with the base name, symbol and URI used for edition metadata and the default
+    /// royalty with the wallet it is paid to, and the unix-time mint window (0 leaves a bound open).
+    /// A sized collection caps the supply at its size, which an uncapped supply of 0 also takes.
+    /// The royalty wallet must be the collection's primary creator, as in `update_royalties`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        master_mint: Pubkey,
+        max_supply: u64,
+        payment_mint: Pubkey,
+        token_price: u64,
+        base_name: String,
+        symbol: String,
+        uri: String,
+        royalty_basis_points: u16,
+        royalty_recipient: Pubkey,
+        mint_start_ts: i64,
+        mint_end_ts: i64,
+    ) -> Result<()> {
+        require_valid_metadata_config(&base_name, &symbol, &uri)?;
+        require!(royalty_basis_points <= 10_000, ErrorCode::InvalidBasisPoints);
+        require_valid_mint_window(mint_start_ts, mint_end_ts)?;
+        
+        let collection = read_edition_metadata(&ctx.accounts.master_metadata, &master_mint)?;
+        require_primary_creator(&collection, royalty_recipient)?;
+        let max_supply = collection_supply_cap(&collection, max_supply)?;
+        
         let config = &mut ctx.accounts.config;
         config.authority = ctx.accounts.authority.key();
         config.master_mint = master_mint;
+        config.max_supply = max_supply;
         config.mint_price = 200_000_000; // 0.2 SOL in lamports
         config.discounted_price = 100_000_000; // 0.1 SOL in lamports (for dapp mints)
+        config.payment_mint = payment_mint;
+        config.token_price = token_price;
         config.total_minted = 0;
         config.payment_vault = ctx.accounts.payment_vault.key();
+        config.is_mutable = true;
+        config.allow_immutable = true;
+        config.min_minter_balance = 0;
+        config.merkle_tree = Pubkey::default();
+        config.creator_wallet = ctx.accounts.authority.key();
+        config.auto_sweep_threshold = 0;
+        config.price_change_cooldown = 0;
+        config.last_price_change_ts = 0;
+        config.referral_basis_points = 0;
+        config.max_referral_mints = 0;
+        config.referral_cap_rejects = false;
+        config.auto_list_enabled = false;
+        config.list_escrow = Pubkey::default();
+        config.finalized = false;
+        config.custodian = None;
+        config.end_slot = 0;
+        config.allow_admin_adjust = false;
+        config.rarity_tiers = Vec::new();
+        config.paused = false;
+        config.print_master = Pubkey::default();
+        config.max_per_wallet = 0;
+        config.prints_minted = 0;
+        config.gate_collection = None;
+        config.gate_update_authority = Pubkey::default();
+        config.min_effective_price = 0;
+        config.base_name = base_name;
+        config.symbol = symbol;
+        config.uri = uri;
+        config.royalty_basis_points = royalty_basis_points;
+        config.royalty_recipient = royalty_recipient;
+        config.pending_authority = None;
+        config.mint_start_ts = mint_start_ts;
+        config.mint_end_ts = mint_end_ts;
+        config.merkle_root = [0; 32];
+        config.usd_price_cents = 0;
+        config.price_feed = Pubkey::default();
+        config.max_price_age = 0;
+        config.vesting_start = 0;
+        config.vesting_duration = 0;
+        config.total_withdrawn = 0;
+        config.genesis_mint = None;
+        config.price_grace_period = 0;
+        config.previous_mint_price = 0;
+        config.price_locked_until = 0;
+        config.staking_program = None;
+        config.min_stake = 0;
+        config.free_supply = 0;
+        config.free_minted = 0;
+        config.holder_max_per_wallet = 0;
+        config.freeze_on_mint = false;
+        config.unlock_ts = 0;
+        config.milestone_interval = 0;
+        config.milestones = Vec::new();
+        config.reveal_base_uri = String::new();
+        config.redeemable_mint = None;
+        config.curve_enabled = false;
+        config.base_price = 0;
+        config.price_increment = 0;
+        config.total_burned = 0;
+        config.royalty_split_wallet = None;
+        config.stake_discriminator = [0; 8];
+        config.price_feed_id = [0; 32];
+        config.max_confidence_bps = 0;
+        config.previous_usd_price_cents = 0;
+        config.reserved_editions = 0;
+        config.reserved_minted = 0;
+        config.max_mints_per_day = 0;
+        config.collection_finalized = false;
+        config.sol_mint_enabled = true;
+        config.token_mint_enabled = true;
+        config.split_sol_price = 0;
+        config.split_token_price = 0;
+        config.discount_mode = DiscountMode::Fixed;
+        config.discount_ratio_bps = 0;
+        config.bulk_tiers = Vec::new();
+        config.max_royalty_bps = 10_000;
+        config.highest_sequential_edition = 0;
         
         msg!("NFT Minter initialized!");
         msg!("Master Mint: {}", master_mint);
+        msg!("Max Supply: {}", max_supply);
         msg!("Regular Mint Price: {} lamports (0.2 SOL)", config.mint_price);
         msg!("Discounted Price: {} lamports (0.1 SOL)", config.discounted_price);
         
@@ -34,258 +213,5552 @@ pub mod nft_minter {
 
     /// Mint a new edition NFT to a user (regular price - for website)
     pub fn mint_edition(ctx: Context<MintEdition>) -> Result<()> {
-        mint_nft_internal(ctx, false)
+        mint_nft_internal(ctx.accounts, Payment::Sol { discounted: false }, None, None, None, None)?;
+        Ok(())
     }
 
-    /// Mint a new edition NFT to a user (discounted price - for dapp)
-    pub fn mint_discounted(ctx: Context<MintEdition>) -> Result<()> {
-        mint_nft_internal(ctx, true)
+    /// Mint a new edition NFT to a user (discounted price - for dapp). Once an allowlist
+    /// root is set, the minter must prove membership with a Merkle proof, or pass the
+    /// allowlist status `register_allowlist` recorded for the current root.
+    pub fn mint_discounted(ctx: Context<MintEdition>, proof: Vec<[u8; 32]>) -> Result<()> {
+        let registered = ctx
+            .accounts
+            .allowlist_status
+            .as_ref()
+            .is_some_and(|status| status.merkle_root == ctx.accounts.config.merkle_root);
+        if !registered {
+            require_allowlisted(&ctx.accounts.config, &ctx.accounts.minter.key(), &proof)?;
+        }
+        mint_nft_internal(ctx.accounts, Payment::Sol { discounted: true }, None, None, None, None)?;
+        Ok(())
     }
 
-    /// Update pricing (only authority)
-    pub fn update_pricing(
-        ctx: Context<UpdateConfig>,
-        new_regular_price: Option<u64>,
-        new_discounted_price: Option<u64>,
-    ) -> Result<()> {
-        let config = &mut ctx.accounts.config;
+    /// Mint a new edition NFT choosing whether its metadata stays mutable (regular price)
+    pub fn mint_with_mutability(ctx: Context<MintEdition>, mutable: bool) -> Result<()> {
+        mint_nft_internal(ctx.accounts, Payment::Sol { discounted: false }, Some(mutable), None, None, None)?;
+        Ok(())
+    }
+
+    /// Mint a specific edition number (regular price). Numbers come from the reserved
+    /// range at the top of `max_supply`, which sequential mints never reach, and each
+    /// can only be claimed once.
+    pub fn mint_edition_at(ctx: Context<MintEditionAt>, edition_number: u64) -> Result<()> {
+        require!(
+            reserved_range(&ctx.accounts.mint.config)?.contains(&edition_number),
+            ErrorCode::InvalidEditionNumber
+        );
+        
+        let edition_index = &mut ctx.accounts.edition_index;
+        require!(!edition_index.claimed, ErrorCode::EditionTaken);
+        edition_index.edition_number = edition_number;
+        edition_index.mint = ctx.accounts.mint.edition_mint.key();
+        edition_index.claimed = true;
+        
+        mint_nft_internal(&mut ctx.accounts.mint, Payment::Sol { discounted: false }, None, Some(edition_number), None, None)?;
+        Ok(())
+    }
+
+    /// Airdrop one edition to each recipient without payment (only authority).
+    /// Remaining accounts hold, per recipient: recipient wallet, edition mint (signer),
+    /// recipient token account, edition metadata and edition.
+    pub fn airdrop_batch<'info>(ctx: Context<'_, '_, '_, 'info, AirdropBatch<'info>>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let recipients = ctx.remaining_accounts;
+        require!(
+            !recipients.is_empty() && recipients.len() % AIRDROP_ACCOUNTS_PER_RECIPIENT == 0,
+            ErrorCode::InvalidAirdropAccounts
+        );
+        require!(
+            recipients.len() / AIRDROP_ACCOUNTS_PER_RECIPIENT <= MAX_AIRDROP_BATCH,
+            ErrorCode::AirdropBatchTooLarge
+        );
         
-        if let Some(price) = new_regular_price {
-            config.mint_price = price;
-            msg!("Updated regular price to: {} lamports", price);
+        let config = &mut ctx.accounts.config;
+        for accounts in recipients.chunks(AIRDROP_ACCOUNTS_PER_RECIPIENT) {
+            require!(accounts[1].is_signer, ErrorCode::InvalidAirdropAccounts);
+            require_edition_pdas(&accounts[1], &accounts[3], &accounts[4])?;
+            require_supply_available(config)?;
+            
+            let edition_number = next_edition_number(config, None)?;
+            
+            create_edition_nft(
+                &EditionAccounts {
+                    creator: ctx.accounts.authority.to_account_info(),
+                    owner: accounts[0].clone(),
+                    edition_mint: accounts[1].clone(),
+                    edition_token_account: accounts[2].clone(),
+                    edition_metadata: accounts[3].clone(),
+                    edition: accounts[4].clone(),
+                    token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                config,
+                edition_number,
+                config.is_mutable,
+            )?;
+            record_genesis_mint(config, accounts[1].key());
+            
+            msg!("Airdropped edition #{} to {}", edition_number, accounts[0].key());
         }
         
-        if let Some(price) = new_discounted_price {
-            config.discounted_price = price;
-            msg!("Updated discounted price to: {} lamports", price);
+        Ok(())
+    }
+
+    /// Mint `quantity` regular-price editions to the minter, paying for all of them up
+    /// front. Remaining accounts hold, per edition: edition mint (signer), minter token
+    /// account, edition metadata and edition.
+    pub fn mint_batch<'info>(ctx: Context<'_, '_, '_, 'info, MintBatch<'info>>, quantity: u8) -> Result<()> {
+        let editions = ctx.remaining_accounts;
+        require!(
+            (1..=MAX_MINT_BATCH).contains(&quantity),
+            ErrorCode::MintBatchTooLarge
+        );
+        require!(
+            editions.len() == quantity as usize * MINT_BATCH_ACCOUNTS_PER_EDITION,
+            ErrorCode::InvalidMintBatchAccounts
+        );
+        
+        let config = &mut ctx.accounts.config;
+        // Both delegate each edition, which this batch path does not do
+        require!(config.custodian.is_none(), ErrorCode::InvalidCustodian);
+        require!(!config.freeze_on_mint, ErrorCode::MintLocked);
+        
+        begin_mint(
+            config,
+            MintCharge {
+                minter: ctx.accounts.minter.to_account_info(),
+                payment_vault: ctx.accounts.payment_vault.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                minter_record: &mut ctx.accounts.minter_record,
+                price_feed: ctx.accounts.price_feed.as_ref(),
+                holder_token_account: ctx.accounts.holder_token_account.as_ref(),
+                holder_metadata: ctx.accounts.holder_metadata.as_ref(),
+                referral: None,
+            },
+            &Payment::Sol { discounted: false },
+            quantity as u64,
+        )?;
+        
+        for accounts in editions.chunks(MINT_BATCH_ACCOUNTS_PER_EDITION) {
+            require!(
+                accounts[0].is_signer && accounts[0].data_is_empty(),
+                ErrorCode::InvalidMintBatchAccounts
+            );
+            require_edition_pdas(&accounts[0], &accounts[2], &accounts[3])?;
+            
+            let price = sol_price(config, ctx.accounts.price_feed.as_ref(), false)?;
+            let edition_number = next_edition_number(config, None)?;
+            
+            create_edition_nft(
+                &EditionAccounts {
+                    creator: ctx.accounts.minter.to_account_info(),
+                    owner: ctx.accounts.minter.to_account_info(),
+                    edition_mint: accounts[0].clone(),
+                    edition_token_account: accounts[1].clone(),
+                    edition_metadata: accounts[2].clone(),
+                    edition: accounts[3].clone(),
+                    token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+                    token_program: ctx.accounts.token_program.to_account_info(),
+                    system_program: ctx.accounts.system_program.to_account_info(),
+                    rent: ctx.accounts.rent.to_account_info(),
+                },
+                config,
+                edition_number,
+                config.is_mutable,
+            )?;
+            record_genesis_mint(config, accounts[0].key());
+            
+            msg!("Minting REGULAR edition #{} for {}", edition_number, ctx.accounts.minter.key());
+            emit_edition_minted(
+                &config.master_mint,
+                ctx.accounts.minter.key(),
+                accounts[0].key(),
+                edition_number,
+                price,
+                false,
+            );
         }
         
         Ok(())
     }
 
-    /// Withdraw collected funds (only authority)
-    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+    /// Mint an edition carrying an authority pre-signed metadata hash (regular price)
+    pub fn mint_with_metadata_hash(
+        ctx: Context<MintWithReceipt>,
+        metadata_hash: [u8; 32],
+    ) -> Result<()> {
+        mint_nft_internal(&mut ctx.accounts.mint, Payment::Sol { discounted: false }, None, None, None, None)?;
+        
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.mint = ctx.accounts.mint.edition_mint.key();
+        receipt.minter = ctx.accounts.mint.minter.key();
+        receipt.edition_number = sequential_minted(&ctx.accounts.mint.config);
+        receipt.metadata_hash = metadata_hash;
+        receipt.reveal_hash = [0; 32];
+        receipt.serial_hash = edition_serial_hash(&ctx.accounts.mint.config.master_mint, receipt.edition_number);
+        receipt.bump = ctx.bumps.receipt;
+        let config = &ctx.accounts.mint.config;
+        receipt.content_hash = edition_content_hash(
+            &edition_name(config, receipt.edition_number)?,
+            &config.symbol,
+            &config.uri,
+        );
+        
+        msg!("Recorded metadata hash for edition #{}", receipt.edition_number);
+        
+        Ok(())
+    }
+
+    /// Check an expected metadata hash against an edition's mint receipt
+    pub fn verify_metadata_hash(ctx: Context<VerifyReceipt>, expected: [u8; 32]) -> Result<()> {
+        require!(
+            ctx.accounts.receipt.metadata_hash == expected,
+            ErrorCode::MetadataHashMismatch
+        );
+        
+        msg!("Metadata hash verified for edition #{}", ctx.accounts.receipt.edition_number);
+        
+        Ok(())
+    }
+
+    /// Mint an edition crediting a referral code with the referral cut (regular price)
+    pub fn mint_with_referral(ctx: Context<MintWithReferral>, _code: String) -> Result<()> {
+        mint_nft_internal(
+            &mut ctx.accounts.mint,
+            Payment::Sol { discounted: false },
+            None,
+            None,
+            None,
+            Some(&mut ctx.accounts.referral_code),
+        )?;
+        
+        Ok(())
+    }
+
+    /// Mint an edition and move it straight into the configured listing escrow (regular price)
+    pub fn mint_and_list(ctx: Context<MintAndList>) -> Result<()> {
+        require!(ctx.accounts.mint.config.auto_list_enabled, ErrorCode::AutoListDisabled);
+        // A delegated or frozen token could not be moved into the escrow afterwards
+        require!(ctx.accounts.mint.config.custodian.is_none(), ErrorCode::InvalidCustodian);
+        require!(!ctx.accounts.mint.config.freeze_on_mint, ErrorCode::MintLocked);
+        
+        mint_nft_internal(&mut ctx.accounts.mint, Payment::Sol { discounted: false }, None, None, None, None)?;
+        
+        let mint = &ctx.accounts.mint;
+        invoke(
+            &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                mint.minter.key,
+                ctx.accounts.list_escrow.key,
+                mint.edition_mint.key,
+                &anchor_spl::token::ID,
+            ),
+            &[
+                mint.minter.to_account_info(),
+                ctx.accounts.escrow_token_account.to_account_info(),
+                ctx.accounts.list_escrow.to_account_info(),
+                mint.edition_mint.to_account_info(),
+                mint.system_program.to_account_info(),
+                mint.token_program.to_account_info(),
+            ],
+        )?;
+        
+        invoke(
+            &spl_token::instruction::transfer(
+                &anchor_spl::token::ID,
+                mint.edition_token_account.key,
+                ctx.accounts.escrow_token_account.key,
+                mint.minter.key,
+                &[],
+                1,
+            )?,
+            &[
+                mint.edition_token_account.to_account_info(),
+                ctx.accounts.escrow_token_account.to_account_info(),
+                mint.minter.to_account_info(),
+            ],
+        )?;
+        
+        msg!("Edition listed into escrow {}", ctx.accounts.list_escrow.key());
+        
+        Ok(())
+    }
+
+    /// Create a print master whose token the config PDA holds, so prints can be signed
+    /// by the program (only authority)
+    pub fn create_print_master(ctx: Context<CreatePrintMaster>, max_supply: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(max_supply > 0, ErrorCode::InvalidPrintSupply);
+        require_edition_pdas(
+            &ctx.accounts.print_master_mint,
+            &ctx.accounts.print_master_metadata,
+            &ctx.accounts.print_master_edition,
+        )?;
+        
+        let nft = EditionAccounts {
+            creator: ctx.accounts.authority.to_account_info(),
+            owner: ctx.accounts.config.to_account_info(),
+            edition_mint: ctx.accounts.print_master_mint.to_account_info(),
+            edition_token_account: ctx.accounts.master_token_account.to_account_info(),
+            edition_metadata: ctx.accounts.print_master_metadata.to_account_info(),
+            edition: ctx.accounts.print_master_edition.to_account_info(),
+            token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        };
+        create_nft_token(&nft)?;
+        create_nft_metadata(
+            &nft,
+            DataV2 {
+                name: ctx.accounts.config.base_name.clone(),
+                symbol: ctx.accounts.config.symbol.clone(),
+                uri: ctx.accounts.config.uri.clone(),
+                seller_fee_basis_points: ctx.accounts.config.royalty_basis_points,
+                creators: Some(vec![Creator {
+                    address: ctx.accounts.authority.key(),
+                    verified: true,
+                    share: 100,
+                }]),
+                collection: Some(Collection {
+                    verified: false,
+                    key: ctx.accounts.config.master_mint,
+                }),
+                uses: None,
+            },
+            true,
+        )?;
+        create_nft_master_edition(&nft, max_supply)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.print_master = ctx.accounts.print_master_mint.key();
+        config.prints_minted = 0;
+        
+        msg!("Print master {} created with {} prints", config.print_master, max_supply);
+        
+        Ok(())
+    }
+
+    /// Mint a numbered print of the print master (regular price)
+    pub fn print_edition(ctx: Context<PrintEdition>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.print_master != Pubkey::default(), ErrorCode::PrintsDisabled);
+        // A print's edition PDA is derived like a master edition's
+        require_edition_pdas(
+            &ctx.accounts.print_master_mint,
+            &ctx.accounts.print_master_metadata,
+            &ctx.accounts.print_master_edition,
+        )?;
+        require_edition_pdas(
+            &ctx.accounts.print_mint,
+            &ctx.accounts.print_metadata,
+            &ctx.accounts.print_edition,
+        )?;
+        
+        // Prints count toward the collection's supply and the minter's wallet limit
+        let price = begin_mint(
+            config,
+            MintCharge {
+                minter: ctx.accounts.minter.to_account_info(),
+                payment_vault: ctx.accounts.payment_vault.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                minter_record: &mut ctx.accounts.minter_record,
+                price_feed: ctx.accounts.price_feed.as_ref(),
+                holder_token_account: ctx.accounts.holder_token_account.as_ref(),
+                holder_metadata: ctx.accounts.holder_metadata.as_ref(),
+                referral: None,
+            },
+            &Payment::Sol { discounted: false },
+            1,
+        )?;
+        // A print takes a sequential slot of the supply but keeps its own print number
+        next_edition_number(config, None)?;
+        config.prints_minted = config.prints_minted.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        let print_number = config.prints_minted;
+        emit_edition_minted(
+            &config.print_master,
+            ctx.accounts.minter.key(),
+            ctx.accounts.print_mint.key(),
+            print_number,
+            price,
+            false,
+        );
+        
+        create_nft_token(&EditionAccounts {
+            creator: ctx.accounts.minter.to_account_info(),
+            owner: ctx.accounts.minter.to_account_info(),
+            edition_mint: ctx.accounts.print_mint.to_account_info(),
+            edition_token_account: ctx.accounts.print_token_account.to_account_info(),
+            edition_metadata: ctx.accounts.print_metadata.to_account_info(),
+            edition: ctx.accounts.print_edition.to_account_info(),
+            token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            rent: ctx.accounts.rent.to_account_info(),
+        })?;
+        
+        let marker = (print_number / EDITION_MARKER_BIT_SIZE).to_string();
+        require_keys_eq!(
+            ctx.accounts.edition_marker.key(),
+            EditionMarker::find_pda(&ctx.accounts.print_master_mint.key(), &marker).0,
+            ErrorCode::InvalidMetadataAccount
+        );
+        
         let seeds = &[
-            b"payment_vault".as_ref(),
-            &[ctx.bumps.payment_vault],
+            b"config".as_ref(),
+            &[ctx.bumps.config],
         ];
         let signer = &[&seeds[..]];
         
+        // The config PDA owns the master token and becomes the print's update authority
         invoke_signed(
-            &system_instruction::transfer(
-                ctx.accounts.payment_vault.key,
-                ctx.accounts.authority.key,
-                amount,
-            ),
+            &mpl_token_metadata::instructions::MintNewEditionFromMasterEditionViaToken {
+                new_metadata: ctx.accounts.print_metadata.key(),
+                new_edition: ctx.accounts.print_edition.key(),
+                master_edition: ctx.accounts.print_master_edition.key(),
+                new_mint: ctx.accounts.print_mint.key(),
+                edition_mark_pda: ctx.accounts.edition_marker.key(),
+                new_mint_authority: ctx.accounts.minter.key(),
+                payer: ctx.accounts.minter.key(),
+                token_account_owner: ctx.accounts.config.key(),
+                token_account: ctx.accounts.master_token_account.key(),
+                new_metadata_update_authority: ctx.accounts.config.key(),
+                metadata: ctx.accounts.print_master_metadata.key(),
+                token_program: ctx.accounts.token_program.key(),
+                system_program: ctx.accounts.system_program.key(),
+                rent: Some(ctx.accounts.rent.key()),
+            }
+            .instruction(mpl_token_metadata::instructions::MintNewEditionFromMasterEditionViaTokenInstructionArgs {
+                mint_new_edition_from_master_edition_via_token_args: MintNewEditionFromMasterEditionViaTokenArgs {
+                    edition: print_number,
+                },
+            }),
             &[
-                ctx.accounts.payment_vault.to_account_info(),
-                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.print_metadata.to_account_info(),
+                ctx.accounts.print_edition.to_account_info(),
+                ctx.accounts.print_master_edition.to_account_info(),
+                ctx.accounts.print_mint.to_account_info(),
+                ctx.accounts.edition_marker.to_account_info(),
+                ctx.accounts.minter.to_account_info(),
+                ctx.accounts.config.to_account_info(),
+                ctx.accounts.master_token_account.to_account_info(),
+                ctx.accounts.print_master_metadata.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
                 ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.rent.to_account_info(),
             ],
             signer,
         )?;
         
-        msg!("Withdrawn {} lamports to authority", amount);
+        msg!("Printed edition #{} for {}", print_number, ctx.accounts.minter.key());
         
         Ok(())
     }
-}
 
-/// Internal helper function to mint NFT with price selection
-fn mint_nft_internal(ctx: Context<MintEdition>, is_discounted: bool) -> Result<()> {
-    let config = &mut ctx.accounts.config;
-    
-    // Select price based on mint type
-    let price = if is_discounted {
-        config.discounted_price
-    } else {
-        config.mint_price
-    };
-    
-    // Transfer payment to vault
-    let transfer_ix = system_instruction::transfer(
-        &ctx.accounts.minter.key(),
-        &ctx.accounts.payment_vault.key(),
-        price,
-    );
-    
-    invoke(
-        &transfer_ix,
-        &[
-            ctx.accounts.minter.to_account_info(),
-            ctx.accounts.payment_vault.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-        ],
-    )?;
+    /// Mint a new edition paid in the configured SPL token instead of lamports
+    pub fn mint_with_token(ctx: Context<MintWithToken>) -> Result<()> {
+        let payment = Payment::Token {
+            from: ctx.accounts.minter_token_account.to_account_info(),
+            vault: ctx.accounts.token_vault.to_account_info(),
+        };
+        mint_nft_internal(&mut ctx.accounts.mint, payment, None, None, None, None)?;
+        Ok(())
+    }
 
-    // Increment edition counter
-    config.total_minted += 1;
-    let edition_number = config.total_minted;
+    /// Mint a new edition paid partly in lamports into the payment vault and partly in the
+    /// configured SPL token into the token vault, at the split prices
+    pub fn mint_split_payment(ctx: Context<MintWithToken>) -> Result<()> {
+        let config = &ctx.accounts.mint.config;
+        require!(
+            config.split_sol_price > 0 && config.split_token_price > 0,
+            ErrorCode::SplitPaymentDisabled
+        );
+        let payment = Payment::Split {
+            from: ctx.accounts.minter_token_account.to_account_info(),
+            vault: ctx.accounts.token_vault.to_account_info(),
+        };
+        mint_nft_internal(&mut ctx.accounts.mint, payment, None, None, None, None)?;
+        Ok(())
+    }
 
-    let mint_type = if is_discounted { "DISCOUNTED" } else { "REGULAR" };
-    msg!("Minting {} edition #{} for {}", mint_type, edition_number, ctx.accounts.minter.key());
-    msg!("Payment of {} lamports received", price);
+    /// Mint a new edition by burning one token of the configured redeemable voucher mint
+    pub fn mint_by_burning(ctx: Context<MintByBurning>) -> Result<()> {
+        let payment = Payment::Voucher {
+            from: ctx.accounts.voucher_token_account.to_account_info(),
+            mint: ctx.accounts.voucher_mint.to_account_info(),
+            decimals: ctx.accounts.voucher_mint.decimals,
+        };
+        mint_nft_internal(&mut ctx.accounts.mint, payment, None, None, None, None)?;
+        Ok(())
+    }
 
-    // Create mint account
-    let mint_rent = Rent::get()?.minimum_balance(82);
-    invoke(
-        &system_instruction::create_account(
-            ctx.accounts.minter.key,
-            ctx.accounts.edition_mint.key,
-            mint_rent,
-            82,
-            &anchor_spl::token::ID,
-        ),
-        &[
-            ctx.accounts.minter.to_account_info(),
-            ctx.accounts.edition_mint.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-        ],
-    )?;
+    /// Mint an edition for a user while a relayer signs and pays. The user authorizes the
+    /// mint off-chain by signing the edition mint's address, checked through an Ed25519
+    /// program instruction placed immediately before this one.
+    pub fn mint_relayed(ctx: Context<MintRelayed>) -> Result<()> {
+        // Custodian and freeze delegation need the token owner's signature, which a relayed
+        // user never gives
+        require!(ctx.accounts.mint.config.custodian.is_none(), ErrorCode::InvalidCustodian);
+        require!(!ctx.accounts.mint.config.freeze_on_mint, ErrorCode::MintLocked);
+        require_relay_authorization(
+            &ctx.accounts.instructions,
+            &ctx.accounts.user.key(),
+            ctx.accounts.mint.edition_mint.key().as_ref(),
+        )?;
+        
+        let user = ctx.accounts.user.to_account_info();
+        mint_nft_internal(&mut ctx.accounts.mint, Payment::Sol { discounted: false }, None, None, Some(user), None)?;
+        msg!("Relayed by {}", ctx.accounts.mint.minter.key());
+        Ok(())
+    }
 
-    // Initialize mint
-    invoke(
-        &spl_token::instruction::initialize_mint(
-            &anchor_spl::token::ID,
-            ctx.accounts.edition_mint.key,
-            ctx.accounts.minter.key,
-            Some(ctx.accounts.minter.key),
-            0,
-        )?,
-        &[
-            ctx.accounts.edition_mint.to_account_info(),
-            ctx.accounts.rent.to_account_info(),
-        ],
-    )?;
+    /// Mint an edition into a program-owned recipient such as a smart wallet PDA. The
+    /// minter signs and pays; the recipient's token account is an off-curve ATA.
+    pub fn mint_to_pda(ctx: Context<MintToPda>, recipient_pda: Pubkey) -> Result<()> {
+        // Custodian delegation needs the token owner's signature, which a PDA cannot give here
+        require!(ctx.accounts.mint.config.custodian.is_none(), ErrorCode::InvalidCustodian);
+        require!(!ctx.accounts.mint.config.freeze_on_mint, ErrorCode::MintLocked);
+        
+        let recipient = ctx.accounts.recipient.to_account_info();
+        mint_nft_internal(&mut ctx.accounts.mint, Payment::Sol { discounted: false }, None, None, Some(recipient), None)?;
+        msg!("Minted to PDA recipient {}", recipient_pda);
+        Ok(())
+    }
 
-    // Create associated token account
-    invoke(
-        &spl_associated_token_account::instruction::create_associated_token_account(
-            ctx.accounts.minter.key,
-            ctx.accounts.minter.key,
-            ctx.accounts.edition_mint.key,
-            &anchor_spl::token::ID,
-        ),
-        &[
-            ctx.accounts.minter.to_account_info(),
-            ctx.accounts.edition_token_account.to_account_info(),
-            ctx.accounts.minter.to_account_info(),
-            ctx.accounts.edition_mint.to_account_info(),
-            ctx.accounts.system_program.to_account_info(),
-            ctx.accounts.token_program.to_account_info(),
-        ],
-    )?;
+    /// Mint a frozen edition for a fiat purchase whose payment settles off-chain. The
+    /// authority co-signs, no lamports are charged, and the config PDA becomes the
+    /// token's delegate and close authority so it can later thaw or burn the edition.
+    pub fn mint_pending(ctx: Context<MintPending>) -> Result<()> {
+        let edition_number = mint_frozen_edition(&mut ctx.accounts.mint, ctx.bumps.mint.config)?;
+        let accounts = &ctx.accounts.mint;
+        
+        let pending = &mut ctx.accounts.pending;
+        pending.mint = accounts.edition_mint.key();
+        pending.minter = accounts.minter.key();
+        pending.edition_number = edition_number;
+        pending.bump = ctx.bumps.pending;
+        
+        msg!("Pending edition #{} minted frozen for {}", edition_number, pending.minter);
+        
+        Ok(())
+    }
 
-    // Mint 1 token
-    invoke(
-        &spl_token::instruction::mint_to(
-            &anchor_spl::token::ID,
-            ctx.accounts.edition_mint.key,
-            ctx.accounts.edition_token_account.key,
-            ctx.accounts.minter.key,
-            &[],
-            1,
-        )?,
-        &[
-            ctx.accounts.edition_mint.to_account_info(),
-            ctx.accounts.edition_token_account.to_account_info(),
-            ctx.accounts.minter.to_account_info(),
-        ],
-    )?;
+    /// Thaw a pending edition once its payment is confirmed, handing the token account's
+    /// close authority back to the minter (only authority)
+    pub fn settle_mint(ctx: Context<ResolvePending>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config_info = ctx.accounts.config.to_account_info();
+        set_edition_frozen(
+            &config_info,
+            ctx.bumps.config,
+            &ctx.accounts.edition_token_account.to_account_info(),
+            &ctx.accounts.edition,
+            &ctx.accounts.edition_mint,
+            &ctx.accounts.token_program,
+            false,
+        )?;
+        
+        let seeds = &[
+            b"config".as_ref(),
+            &[ctx.bumps.config],
+        ];
+        let signer = &[&seeds[..]];
+        anchor_spl::token::set_authority(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::SetAuthority {
+                    current_authority: config_info,
+                    account_or_mint: ctx.accounts.edition_token_account.to_account_info(),
+                },
+                signer,
+            ),
+            AuthorityType::CloseAccount,
+            None,
+        )?;
+        
+        msg!("Settled pending edition #{}", ctx.accounts.pending.edition_number);
+        
+        Ok(())
+    }
 
-    // Create metadata account with collection reference
-    let metadata_infos = vec![
-        ctx.accounts.edition_metadata.to_account_info(),
-        ctx.accounts.edition_mint.to_account_info(),
-        ctx.accounts.minter.to_account_info(),
-        ctx.accounts.minter.to_account_info(),
-        ctx.accounts.minter.to_account_info(),
-        ctx.accounts.system_program.to_account_info(),
-        ctx.accounts.rent.to_account_info(),
-    ];
+    /// Burn a pending edition whose payment never arrived and close its token account
+    /// (only authority). The token account and pending record rent return to the minter;
+    /// the metadata and edition accounts stay, as closing them needs the owner's signature.
+    pub fn cancel_pending(ctx: Context<ResolvePending>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config_info = ctx.accounts.config.to_account_info();
+        set_edition_frozen(
+            &config_info,
+            ctx.bumps.config,
+            &ctx.accounts.edition_token_account.to_account_info(),
+            &ctx.accounts.edition,
+            &ctx.accounts.edition_mint,
+            &ctx.accounts.token_program,
+            false,
+        )?;
+        
+        let seeds = &[
+            b"config".as_ref(),
+            &[ctx.bumps.config],
+        ];
+        let signer = &[&seeds[..]];
+        anchor_spl::token::burn(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Burn {
+                    mint: ctx.accounts.edition_mint.to_account_info(),
+                    from: ctx.accounts.edition_token_account.to_account_info(),
+                    authority: config_info.clone(),
+                },
+                signer,
+            ),
+            1,
+        )?;
+        anchor_spl::token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::CloseAccount {
+                account: ctx.accounts.edition_token_account.to_account_info(),
+                destination: ctx.accounts.minter.to_account_info(),
+                authority: config_info,
+            },
+            signer,
+        ))?;
+        
+        msg!("Cancelled pending edition #{}", ctx.accounts.pending.edition_number);
+        
+        Ok(())
+    }
 
-    let creators = vec![Creator {
-        address: ctx.accounts.minter.key(),
-        verified: true,
-        share: 100,
-    }];
+    /// Mint a team allocation held in escrow until its vesting end (authority co-signs).
+    /// The edition goes to a token account owned by the vesting PDA, so the recipient
+    /// does not sign; the minter pays the account rent.
+    pub fn mint_vested(ctx: Context<MintVested>, vesting_end: i64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.mint.config.key())?;
+        require!(
+            vesting_end > Clock::get()?.unix_timestamp,
+            ErrorCode::InvalidVestingSchedule
+        );
+        
+        let escrow = ctx.accounts.vesting.to_account_info();
+        let edition_number = mint_unpaid_edition(&mut ctx.accounts.mint, escrow)?;
+        
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.mint = ctx.accounts.mint.edition_mint.key();
+        vesting.recipient = ctx.accounts.recipient.key();
+        vesting.vesting_end = vesting_end;
+        vesting.bump = ctx.bumps.vesting;
+        
+        msg!("Vested edition #{} escrowed for {} until {}", edition_number, vesting.recipient, vesting_end);
+        
+        Ok(())
+    }
 
-    let collection = Some(Collection {
-        verified: false,
-        key: config.master_mint,
-    });
+    /// Thaw an edition frozen on mint once the config's unlock time has passed
+    pub fn unfreeze(ctx: Context<Unfreeze>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.config.unlock_ts,
+            ErrorCode::MintLocked
+        );
+        
+        set_edition_frozen(
+            &ctx.accounts.config.to_account_info(),
+            ctx.bumps.config,
+            &ctx.accounts.edition_token_account.to_account_info(),
+            &ctx.accounts.edition,
+            &ctx.accounts.edition_mint,
+            &ctx.accounts.token_program,
+            false,
+        )?;
+        
+        msg!("Edition {} unfrozen for {}", ctx.accounts.edition_mint.key(), ctx.accounts.owner.key());
+        
+        Ok(())
+    }
 
-    invoke(
-        &mpl_token_metadata::instructions::CreateMetadataAccountV3 {
-            metadata: ctx.accounts.edition_metadata.key(),
-            mint: ctx.accounts.edition_mint.key(),
-            mint_authority: ctx.accounts.minter.key(),
-            payer: ctx.accounts.minter.key(),
-            update_authority: (ctx.accounts.minter.key(), true),
-            system_program: ctx.accounts.system_program.key(),
-            rent: Some(ctx.accounts.rent.key()),
-        }
-        .instruction(mpl_token_metadata::instructions::CreateMetadataAccountV3InstructionArgs {
-            data: DataV2 {
-                name: format!("AMMo Founder #{}", edition_number),
-                symbol: "FAMMo".to_string(),
-                uri: "https://plum-imperial-swordfish-193.mypinata.cloud/ipfs/bafkreiddegzxdo2h3sliwjfpp22f46mfwb7frb3aibdqtln74uiiv3wkmy".to_string(),
-                seller_fee_basis_points: 500,
-                creators: Some(creators),
-                collection,
-                uses: None,
-            },
-            is_mutable: true,
-            collection_details: None,
-        }),
-        metadata_infos.as_slice(),
-    )?;
+    /// Freeze an edition for moderation (only authority), recording a moderation hold
+    /// that keeps `unfreeze` from thawing it until `admin_thaw`.
+    /// CreateMasterEditionV3 moves each edition mint's freeze authority to its edition
+    /// PDA, so a program-wide `[b"freeze_authority"]` PDA is impossible: only editions
+    /// whose token account delegates to the config PDA can be moderated. Those are the
+    /// editions frozen on mint and pending editions; any other edition, or one whose holder
+    /// has since revoked or replaced the delegate, fails with `EditionNotModeratable`.
+    pub fn admin_freeze(ctx: Context<AdminFreeze>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        set_edition_frozen(
+            &ctx.accounts.config.to_account_info(),
+            ctx.bumps.config,
+            &ctx.accounts.edition_token_account.to_account_info(),
+            &ctx.accounts.edition,
+            &ctx.accounts.edition_mint,
+            &ctx.accounts.token_program,
+            true,
+        )?;
+        
+        let moderation = &mut ctx.accounts.moderation;
+        moderation.mint = ctx.accounts.edition_mint.key();
+        moderation.frozen_at = Clock::get()?.unix_timestamp;
+        moderation.bump = ctx.bumps.moderation;
+        
+        msg!("Edition {} frozen by authority", ctx.accounts.edition_mint.key());
+        
+        Ok(())
+    }
 
-    // Create master edition
-    let master_edition_infos = vec![
-        ctx.accounts.edition.to_account_info(),
-        ctx.accounts.edition_mint.to_account_info(),
-        ctx.accounts.minter.to_account_info(),
-        ctx.accounts.minter.to_account_info(),
-        ctx.accounts.edition_metadata.to_account_info(),
-        ctx.accounts.token_metadata_program.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        ctx.accounts.system_program.to_account_info(),
-        ctx.accounts.rent.to_account_info(),
-    ];
+    /// Thaw an edition frozen through the config PDA's delegation (only authority),
+    /// lifting its moderation hold when it has one
+    pub fn admin_thaw(ctx: Context<AdminThaw>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        set_edition_frozen(
+            &ctx.accounts.config.to_account_info(),
+            ctx.bumps.config,
+            &ctx.accounts.edition_token_account.to_account_info(),
+            &ctx.accounts.edition,
+            &ctx.accounts.edition_mint,
+            &ctx.accounts.token_program,
+            false,
+        )?;
+        
+        msg!("Edition {} thawed by authority", ctx.accounts.edition_mint.key());
+        
+        Ok(())
+    }
 
-    invoke(
-        &mpl_token_metadata::instructions::CreateMasterEditionV3 {
-            edition: ctx.accounts.edition.key(),
-            mint: ctx.accounts.edition_mint.key(),
-            update_authority: ctx.accounts.minter.key(),
-            mint_authority: ctx.accounts.minter.key(),
-            payer: ctx.accounts.minter.key(),
-            metadata: ctx.accounts.edition_metadata.key(),
-            token_program: ctx.accounts.token_program.key(),
-            system_program: ctx.accounts.system_program.key(),
-            rent: Some(ctx.accounts.rent.key()),
+    /// Burn an edition held by the signer. The token is burned and its account closed;
+    /// with `burn_metadata` Token Metadata's BurnNft also closes the metadata and master
+    /// edition. Burns are counted separately so `total_minted` keeps numbering editions.
+    pub fn burn_edition(ctx: Context<BurnEdition>, burn_metadata: bool) -> Result<()> {
+        let metadata = read_edition_metadata(
+            &ctx.accounts.edition_metadata,
+            &ctx.accounts.edition_mint.key(),
+        )?;
+        require!(
+            metadata
+                .collection
+                .is_some_and(|collection| collection.verified && collection.key == ctx.accounts.config.master_mint),
+            ErrorCode::NotEditionOwner
+        );
+        
+        if burn_metadata {
+            let collection_metadata = ctx.accounts.collection_metadata.as_ref();
+            let mut account_infos = vec![
+                ctx.accounts.edition_metadata.to_account_info(),
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.edition_mint.to_account_info(),
+                ctx.accounts.edition_token_account.to_account_info(),
+                ctx.accounts.edition.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+            ];
+            account_infos.extend(collection_metadata.cloned());
+            
+            invoke(
+                &mpl_token_metadata::instructions::BurnNft {
+                    metadata: ctx.accounts.edition_metadata.key(),
+                    owner: ctx.accounts.owner.key(),
+                    mint: ctx.accounts.edition_mint.key(),
+                    token_account: ctx.accounts.edition_token_account.key(),
+                    master_edition_account: ctx.accounts.edition.key(),
+                    spl_token_program: ctx.accounts.token_program.key(),
+                    collection_metadata: collection_metadata.map(|info| info.key()),
+                }
+                .instruction(),
+                &account_infos,
+            )?;
+        } else {
+            anchor_spl::token::burn(
+                CpiContext::new(
+                    ctx.accounts.token_program.to_account_info(),
+                    anchor_spl::token::Burn {
+                        mint: ctx.accounts.edition_mint.to_account_info(),
+                        from: ctx.accounts.edition_token_account.to_account_info(),
+                        authority: ctx.accounts.owner.to_account_info(),
+                    },
+                ),
+                1,
+            )?;
+            anchor_spl::token::close_account(CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::CloseAccount {
+                    account: ctx.accounts.edition_token_account.to_account_info(),
+                    destination: ctx.accounts.owner.to_account_info(),
+                    authority: ctx.accounts.owner.to_account_info(),
+                },
+            ))?;
         }
-        .instruction(mpl_token_metadata::instructions::CreateMasterEditionV3InstructionArgs {
-            max_supply: Some(0),
-        }),
-        master_edition_infos.as_slice(),
-    )?;
+        
+        let config = &mut ctx.accounts.config;
+        config.total_burned = config.total_burned.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        
+        msg!("Edition {} burned by {}", ctx.accounts.edition_mint.key(), ctx.accounts.owner.key());
+        
+        Ok(())
+    }
+
+    /// Release a vested edition from escrow to its recipient once its vesting end has
+    /// passed. The escrow token account and vesting record rent go to the recipient.
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        require!(
+            Clock::get()?.unix_timestamp >= ctx.accounts.vesting.vesting_end,
+            ErrorCode::VestingNotEnded
+        );
+        
+        let edition_mint = ctx.accounts.edition_mint.key();
+        let seeds = &[
+            b"vesting".as_ref(),
+            edition_mint.as_ref(),
+            &[ctx.accounts.vesting.bump],
+        ];
+        let signer = &[&seeds[..]];
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.escrow_token_account.to_account_info(),
+                    to: ctx.accounts.recipient_token_account.to_account_info(),
+                    authority: ctx.accounts.vesting.to_account_info(),
+                },
+                signer,
+            ),
+            1,
+        )?;
+        anchor_spl::token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            anchor_spl::token::CloseAccount {
+                account: ctx.accounts.escrow_token_account.to_account_info(),
+                destination: ctx.accounts.recipient.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer,
+        ))?;
+        
+        msg!("Vested edition {} claimed by {}", edition_mint, ctx.accounts.recipient.key());
+        
+        Ok(())
+    }
+
+    /// Register a referral code owned by the calling affiliate
+    pub fn create_referral_code(ctx: Context<CreateReferralCode>, code: String) -> Result<()> {
+        require!(
+            !code.is_empty() && code.len() <= MAX_REFERRAL_CODE_LEN,
+            ErrorCode::InvalidReferralCode
+        );
+        
+        let referral = &mut ctx.accounts.referral_code;
+        referral.owner = ctx.accounts.owner.key();
+        referral.code = code;
+        referral.unclaimed = 0;
+        referral.total_earned = 0;
+        referral.mints = 0;
+        referral.bump = ctx.bumps.referral_code;
+        
+        msg!("Referral code {} registered to {}", referral.code, referral.owner);
+        
+        Ok(())
+    }
+
+    /// Withdraw accrued referral rewards to the code owner
+    pub fn claim_referral(ctx: Context<ClaimReferral>, code: String) -> Result<()> {
+        let referral = &mut ctx.accounts.referral_code;
+        let amount = referral.unclaimed;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+        
+        referral.unclaimed = 0;
+        **referral.to_account_info().try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.owner.to_account_info().try_borrow_mut_lamports()? += amount;
+        
+        msg!("Referral code {} claimed {} lamports", code, amount);
+        
+        Ok(())
+    }
+
+    /// Succeeds only if the signer holds an edition of this collection; for CPI perk gating
+    pub fn assert_owns_edition(ctx: Context<AssertOwnsEdition>) -> Result<()> {
+        let token_account = &ctx.accounts.token_account;
+        require!(
+            token_account.owner == ctx.accounts.owner.key() && token_account.amount >= 1,
+            ErrorCode::NotEditionOwner
+        );
+        
+        let metadata = read_edition_metadata(&ctx.accounts.edition_metadata, &token_account.mint)?;
+        require!(
+            metadata
+                .collection
+                .is_some_and(|collection| collection.verified && collection.key == ctx.accounts.config.master_mint),
+            ErrorCode::NotEditionOwner
+        );
+        
+        msg!("{} owns edition {}", ctx.accounts.owner.key(), token_account.mint);
+        
+        Ok(())
+    }
+
+    /// Mint an edition for holders of the gate collection. The gate NFT must be a verified
+    /// member and carry the expected update authority, so a forged collection key is refused.
+    pub fn mint_gated(ctx: Context<MintGated>) -> Result<()> {
+        let config = &ctx.accounts.mint.config;
+        let gate_collection = config.gate_collection.ok_or(ErrorCode::GateDisabled)?;
+        
+        let gate_token_account = &ctx.accounts.gate_token_account;
+        require!(gate_token_account.amount >= 1, ErrorCode::InvalidGateNft);
+        
+        let metadata = read_edition_metadata(&ctx.accounts.gate_metadata, &gate_token_account.mint)?;
+        require!(
+            metadata
+                .collection
+                .is_some_and(|collection| collection.verified && collection.key == gate_collection),
+            ErrorCode::InvalidGateNft
+        );
+        require_keys_eq!(metadata.update_authority, config.gate_update_authority, ErrorCode::InvalidGateNft);
+        
+        msg!("Gate NFT {} accepted", gate_token_account.mint);
+        mint_nft_internal(&mut ctx.accounts.mint, Payment::Sol { discounted: false }, None, None, None, None)?;
+        Ok(())
+    }
+
+    /// Claim one of the `free_supply` public free editions; once they are gone, minters
+    /// fall back to the paid instructions
+    pub fn mint_free(ctx: Context<MintEdition>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(config.free_minted < config.free_supply, ErrorCode::FreeSupplyExhausted);
+        config.free_minted = config.free_minted.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        
+        mint_nft_internal(ctx.accounts, Payment::Free, None, None, None, None)?;
+        Ok(())
+    }
+
+    /// Mint at the discounted price for wallets holding at least `min_stake` in the
+    /// configured staking program
+    pub fn mint_for_stakers(ctx: Context<MintForStakers>) -> Result<()> {
+        require_staking(
+            &ctx.accounts.mint.config,
+            &ctx.accounts.mint.minter.key(),
+            &ctx.accounts.stake_account,
+        )?;
+        mint_nft_internal(&mut ctx.accounts.mint, Payment::Sol { discounted: true }, None, None, None, None)?;
+        Ok(())
+    }
+
+    /// Return how many editions `wallet` minted, 0 when it never minted
+    pub fn get_minter_count(ctx: Context<GetMinterCount>, wallet: Pubkey) -> Result<u64> {
+        let record_info = &ctx.accounts.minter_record;
+        let count = if record_info.data_is_empty() {
+            0
+        } else {
+            MinterRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?.count
+        };
+        
+        msg!("{} minted {} editions", wallet, count);
+        
+        Ok(count)
+    }
+
+    /// Return `wallet`'s mint history from its minter record, all zeroes when it never minted
+    pub fn get_minter_receipt(ctx: Context<GetMinterCount>, wallet: Pubkey) -> Result<MinterSummary> {
+        read_minter_summary(&ctx.accounts.minter_record, wallet)
+    }
+
+    /// Return the mint history of several wallets in one call, each passed in the remaining
+    /// accounts as its wallet followed by its minter record PDA
+    pub fn get_minter_records<'info>(
+        ctx: Context<'_, '_, '_, 'info, GetMinterRecords<'info>>,
+    ) -> Result<Vec<MinterSummary>> {
+        let minters = ctx.remaining_accounts;
+        require!(
+            minters.len() % MINTER_RECORD_READ_ACCOUNTS == 0,
+            ErrorCode::InvalidMinterRecordAccounts
+        );
+        require!(
+            minters.len() / MINTER_RECORD_READ_ACCOUNTS <= MAX_MINTER_RECORDS_READ,
+            ErrorCode::TooManyMinterRecords
+        );
+        
+        minters
+            .chunks(MINTER_RECORD_READ_ACCOUNTS)
+            .map(|accounts| {
+                let wallet = accounts[0].key();
+                let (expected, _) = Pubkey::find_program_address(&[b"minter", wallet.as_ref()], &crate::ID);
+                require_keys_eq!(accounts[1].key(), expected, ErrorCode::InvalidMinterRecordAccounts);
+                read_minter_summary(&accounts[1], wallet)
+            })
+            .collect()
+    }
+
+    /// Check the passed account is the payment vault PDA derived with `expected_bump`
+    pub fn assert_vault(ctx: Context<AssertVault>, expected_bump: u8) -> Result<()> {
+        let expected = Pubkey::create_program_address(&[b"payment_vault", &[expected_bump]], &crate::ID)
+            .map_err(|_| error!(ErrorCode::InvalidVault))?;
+        require_keys_eq!(ctx.accounts.payment_vault.key(), expected, ErrorCode::InvalidVault);
+        Ok(())
+    }
+
+    /// Return the client-facing config fields, so readers don't depend on the account layout
+    pub fn get_config(ctx: Context<GetConfig>) -> Result<ConfigView> {
+        let config = &ctx.accounts.config;
+        Ok(ConfigView {
+            authority: config.authority,
+            mint_price: config.mint_price,
+            discounted_price: config.discounted_price,
+            token_price: config.token_price,
+            total_minted: config.total_minted,
+            max_supply: config.max_supply,
+            paused: config.paused,
+        })
+    }
+
+    /// Diagnose why `minter` could not mint one edition right now, without minting: returns
+    /// the `ELIGIBILITY_*` bits of every failing check, 0 when eligible. `proof` is checked
+    /// against the discounted mint's allowlist.
+    pub fn eligibility(ctx: Context<Eligibility>, proof: Vec<[u8; 32]>) -> Result<u32> {
+        let config = &ctx.accounts.config;
+        let clock = Clock::get()?;
+        let mut reasons = 0;
+        
+        if config.paused {
+            reasons |= ELIGIBILITY_PAUSED;
+        }
+        if config.max_supply > 0 && config.total_minted >= config.max_supply {
+            reasons |= ELIGIBILITY_SOLD_OUT;
+        }
+        if config.mint_start_ts > 0 && clock.unix_timestamp < config.mint_start_ts {
+            reasons |= ELIGIBILITY_NOT_STARTED;
+        }
+        if (config.mint_end_ts > 0 && clock.unix_timestamp >= config.mint_end_ts)
+            || (config.end_slot > 0 && clock.slot >= config.end_slot)
+        {
+            reasons |= ELIGIBILITY_ENDED;
+        }
+        if require_allowlisted(config, &ctx.accounts.minter.key(), &proof).is_err() {
+            reasons |= ELIGIBILITY_NOT_ALLOWLISTED;
+        }
+        
+        if let Some(record) = &ctx.accounts.minter_record {
+            let max_per_wallet = wallet_limit(
+                config,
+                ctx.accounts.holder_token_account.as_ref(),
+                ctx.accounts.holder_metadata.as_ref(),
+            )?;
+            if max_per_wallet > 0 && record.count >= max_per_wallet {
+                reasons |= ELIGIBILITY_WALLET_LIMIT;
+            }
+            if config.max_mints_per_day > 0
+                && record.day == clock.unix_timestamp.div_euclid(86_400)
+                && record.daily_count >= config.max_mints_per_day
+            {
+                reasons |= ELIGIBILITY_COOLDOWN;
+            }
+        }
+        
+        msg!("Eligibility of {}: {:#b}", ctx.accounts.minter.key(), reasons);
+        
+        Ok(reasons)
+    }
+
+    /// Return whether every edition of a capped supply has been minted, false when the
+    /// supply is unlimited
+    pub fn is_sold_out(ctx: Context<GetConfig>) -> Result<bool> {
+        let config = &ctx.accounts.config;
+        Ok(config.max_supply != 0 && config.total_minted >= config.max_supply)
+    }
+
+    /// Dry-run `mint_batch`: checks the batch size, that the mint is open, remaining supply,
+    /// the wallet limit (the holder limit when a collection NFT is shown) and that the
+    /// minter can pay, then returns the total lamport cost. Account rent for the editions
+    /// is not included.
+    pub fn validate_batch(ctx: Context<ValidateBatch>, quantity: u64) -> Result<u64> {
+        let config = &ctx.accounts.config;
+        require!(
+            (1..=MAX_MINT_BATCH as u64).contains(&quantity),
+            ErrorCode::MintBatchTooLarge
+        );
+        require_mint_open(config)?;
+        
+        if config.max_supply > 0 {
+            let remaining = sequential_cap(config)?.saturating_sub(sequential_minted(config));
+            if quantity > remaining {
+                msg!("Batch of {} exceeds remaining supply by {}", quantity, quantity - remaining);
+                return err!(ErrorCode::MaxSupplyReached);
+            }
+        }
+        
+        let max_per_wallet = wallet_limit(
+            config,
+            ctx.accounts.holder_token_account.as_ref(),
+            ctx.accounts.holder_metadata.as_ref(),
+        )?;
+        if max_per_wallet > 0 {
+            let minted = ctx.accounts.minter_record.as_ref().map_or(0, |record| record.count);
+            let allowed = max_per_wallet.saturating_sub(minted);
+            if quantity > allowed {
+                msg!("Batch of {} exceeds the wallet limit by {}", quantity, quantity - allowed);
+                return err!(ErrorCode::WalletLimitReached);
+            }
+        }
+        
+        let price = sol_price(config, ctx.accounts.price_feed.as_ref(), false)?;
+        require!(price >= config.min_effective_price, ErrorCode::PriceTooLow);
+        let total_cost = if config.usd_price_cents > 0 {
+            price.checked_mul(quantity).ok_or(ErrorCode::Overflow)?
+        } else {
+            regular_batch_cost(config, quantity)?
+        };
+        let total_cost = bulk_discounted(config, quantity, total_cost);
+        
+        let required = total_cost
+            .checked_add(config.min_minter_balance)
+            .ok_or(ErrorCode::Overflow)?;
+        let balance = ctx.accounts.minter.lamports();
+        if balance < required {
+            msg!("Batch costs {} lamports, minter is short by {}", required, required - balance);
+            return err!(ErrorCode::MinterBalanceTooLow);
+        }
+        
+        msg!("Batch of {} editions costs {} lamports", quantity, total_cost);
+        
+        Ok(total_cost)
+    }
+
+    /// Fail if an edition's on-chain metadata drifted from what its mint receipt recorded:
+    /// the serial hash of its edition number and the hash of the name, symbol and URI it
+    /// was minted (or revealed) with. Editions minted without a receipt cannot be checked.
+    pub fn verify_edition_integrity(ctx: Context<VerifyEditionIntegrity>) -> Result<()> {
+        let metadata = read_edition_metadata(
+            &ctx.accounts.edition_metadata,
+            &ctx.accounts.edition_mint.key(),
+        )?;
+        let data = metadata_data(&metadata);
+        let receipt = &ctx.accounts.receipt;
+        
+        require!(
+            receipt.serial_hash == edition_serial_hash(&ctx.accounts.config.master_mint, receipt.edition_number)
+                && receipt.content_hash == edition_content_hash(&data.name, &data.symbol, &data.uri)
+                && data
+                    .collection
+                    .is_some_and(|collection| collection.key == ctx.accounts.config.master_mint),
+            ErrorCode::MetadataDrift
+        );
+        
+        msg!("Edition #{} metadata matches", receipt.edition_number);
+        
+        Ok(())
+    }
+
+    /// Record how many editions a holder owns at a snapshot epoch (only authority).
+    /// Remaining accounts hold, per edition: the holder's token account and its metadata.
+    pub fn snapshot_holder<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SnapshotHolder<'info>>,
+        epoch: u64,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let editions = ctx.remaining_accounts;
+        require!(
+            editions.len() % SNAPSHOT_ACCOUNTS_PER_EDITION == 0,
+            ErrorCode::InvalidSnapshotAccounts
+        );
+        
+        let holder = ctx.accounts.holder.key();
+        let mut counted: Vec<Pubkey> = Vec::new();
+        for accounts in editions.chunks(SNAPSHOT_ACCOUNTS_PER_EDITION) {
+            let token_account = Account::<TokenAccount>::try_from(&accounts[0])
+                .map_err(|_| error!(ErrorCode::InvalidSnapshotAccounts))?;
+            require!(
+                token_account.owner == holder && token_account.amount >= 1,
+                ErrorCode::InvalidSnapshotAccounts
+            );
+            require!(!counted.contains(&token_account.mint), ErrorCode::InvalidSnapshotAccounts);
+            
+            let metadata = read_edition_metadata(&accounts[1], &token_account.mint)?;
+            require!(
+                metadata
+                    .collection
+                    .is_some_and(|collection| collection.verified && collection.key == ctx.accounts.config.master_mint),
+                ErrorCode::InvalidSnapshotAccounts
+            );
+            counted.push(token_account.mint);
+        }
+        
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.holder = holder;
+        snapshot.epoch = epoch;
+        snapshot.edition_count = counted.len() as u64;
+        snapshot.bump = ctx.bumps.snapshot;
+        
+        msg!("Snapshot {}: {} holds {} editions", epoch, holder, snapshot.edition_count);
+        
+        Ok(())
+    }
+
+    /// Opt-in migration: the edition's current update authority hands it to the config PDA
+    pub fn claim_edition_authority(ctx: Context<ClaimEditionAuthority>) -> Result<()> {
+        let metadata = read_edition_metadata(
+            &ctx.accounts.edition_metadata,
+            &ctx.accounts.edition_mint.key(),
+        )?;
+        require!(
+            metadata
+                .collection
+                .is_some_and(|collection| collection.key == ctx.accounts.config.master_mint),
+            ErrorCode::InvalidMetadataAccount
+        );
+        
+        invoke(
+            &mpl_token_metadata::instructions::UpdateMetadataAccountV2 {
+                metadata: ctx.accounts.edition_metadata.key(),
+                update_authority: ctx.accounts.update_authority.key(),
+            }
+            .instruction(mpl_token_metadata::instructions::UpdateMetadataAccountV2InstructionArgs {
+                data: None,
+                new_update_authority: Some(ctx.accounts.config.key()),
+                primary_sale_happened: None,
+                is_mutable: None,
+            }),
+            &[
+                ctx.accounts.edition_metadata.to_account_info(),
+                ctx.accounts.update_authority.to_account_info(),
+            ],
+        )?;
+        
+        msg!("Update authority of {} handed to config", ctx.accounts.edition_mint.key());
+        
+        Ok(())
+    }
+
+    /// Update the URI of an edition whose update authority is the config PDA (only authority)
+    pub fn update_edition_uri(ctx: Context<UpdateEditionMetadata>, uri: String) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let metadata = read_edition_metadata(
+            &ctx.accounts.edition_metadata,
+            &ctx.accounts.edition_mint.key(),
+        )?;
+        
+        set_edition_uri(
+            &ctx.accounts.config.to_account_info(),
+            ctx.bumps.config,
+            &ctx.accounts.edition_metadata,
+            &metadata,
+            uri,
+        )?;
+        
+        msg!("Updated URI of {}", ctx.accounts.edition_mint.key());
+        
+        Ok(())
+    }
+
+    /// Attach the sha256 hash of an edition's claim code to its mint receipt (only authority)
+    pub fn set_reveal_hash(ctx: Context<SetRevealHash>, reveal_hash: [u8; 32]) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let receipt = &mut ctx.accounts.receipt;
+        receipt.reveal_hash = reveal_hash;
+        
+        msg!("Reveal hash set for edition #{}", receipt.edition_number);
+        
+        Ok(())
+    }
+
+    /// Reveal an edition by presenting its claim code: the URI moves from the placeholder to
+    /// `reveal_base_uri` followed by "<edition>.json". The config PDA must hold the edition's
+    /// update authority, and each code reveals once.
+    pub fn reveal_with_secret(ctx: Context<RevealWithSecret>, secret: Vec<u8>) -> Result<()> {
+        let receipt = &mut ctx.accounts.receipt;
+        require!(
+            receipt.reveal_hash != [0; 32]
+                && anchor_lang::solana_program::hash::hash(&secret).to_bytes() == receipt.reveal_hash,
+            ErrorCode::InvalidRevealSecret
+        );
+        require!(!ctx.accounts.config.reveal_base_uri.is_empty(), ErrorCode::InvalidMetadataConfig);
+        receipt.reveal_hash = [0; 32];
+        
+        let metadata = read_edition_metadata(
+            &ctx.accounts.edition_metadata,
+            &ctx.accounts.edition_mint.key(),
+        )?;
+        let uri = format!("{}{}.json", ctx.accounts.config.reveal_base_uri, receipt.edition_number);
+        // A reveal is the one sanctioned URI change, so the receipt follows it
+        let data = metadata_data(&metadata);
+        receipt.content_hash = edition_content_hash(&data.name, &data.symbol, &uri);
+        set_edition_uri(
+            &ctx.accounts.config.to_account_info(),
+            ctx.bumps.config,
+            &ctx.accounts.edition_metadata,
+            &metadata,
+            uri,
+        )?;
+        
+        msg!("Revealed edition #{} for {}", receipt.edition_number, ctx.accounts.holder.key());
+        
+        Ok(())
+    }
+
+    /// Set the royalty on the collection NFT, signed by the authority or the config PDA
+    pub fn set_collection_royalty(ctx: Context<SetCollectionRoyalty>, basis_points: u16) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(basis_points <= 10_000, ErrorCode::InvalidBasisPoints);
+        require_royalty_within_ceiling(&ctx.accounts.config, basis_points)?;
+        let metadata = read_edition_metadata(
+            &ctx.accounts.master_metadata,
+            &ctx.accounts.config.master_mint,
+        )?;
+        
+        let mut data = metadata_data(&metadata);
+        data.seller_fee_basis_points = basis_points;
+        
+        // The collection may still belong to the authority wallet or already to the config PDA
+        let update_authority = if metadata.update_authority == ctx.accounts.config.key() {
+            ctx.accounts.config.to_account_info()
+        } else {
+            ctx.accounts.authority.to_account_info()
+        };
+        
+        let seeds = &[
+            b"config".as_ref(),
+            &[ctx.bumps.config],
+        ];
+        let signer = &[&seeds[..]];
+        
+        invoke_signed(
+            &mpl_token_metadata::instructions::UpdateMetadataAccountV2 {
+                metadata: ctx.accounts.master_metadata.key(),
+                update_authority: update_authority.key(),
+            }
+            .instruction(mpl_token_metadata::instructions::UpdateMetadataAccountV2InstructionArgs {
+                data: Some(data),
+                new_update_authority: None,
+                primary_sale_happened: None,
+                is_mutable: None,
+            }),
+            &[
+                ctx.accounts.master_metadata.to_account_info(),
+                update_authority,
+            ],
+            signer,
+        )?;
+        
+        msg!("Collection royalty set to {} bps", basis_points);
+        
+        Ok(())
+    }
+
+    /// Verify a minted edition as a member of the master collection (only authority).
+    /// Sized collections go through VerifySizedCollectionItem, unsized ones through VerifyCollection.
+    pub fn verify_collection(ctx: Context<VerifyCollection>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let collection = read_edition_metadata(
+            &ctx.accounts.master_metadata,
+            &ctx.accounts.config.master_mint,
+        )?;
+        read_edition_metadata(&ctx.accounts.edition_metadata, &ctx.accounts.edition_mint.key())?;
+        
+        // The collection may still belong to the authority wallet or already to the config PDA
+        let collection_authority = if collection.update_authority == ctx.accounts.config.key() {
+            ctx.accounts.config.to_account_info()
+        } else {
+            ctx.accounts.authority.to_account_info()
+        };
+        
+        let seeds = &[
+            b"config".as_ref(),
+            &[ctx.bumps.config],
+        ];
+        let signer = &[&seeds[..]];
+        
+        let instruction = if collection.collection_details.is_some() {
+            mpl_token_metadata::instructions::VerifySizedCollectionItem {
+                metadata: ctx.accounts.edition_metadata.key(),
+                collection_authority: collection_authority.key(),
+                payer: ctx.accounts.authority.key(),
+                collection_mint: ctx.accounts.master_mint.key(),
+                collection: ctx.accounts.master_metadata.key(),
+                collection_master_edition_account: ctx.accounts.master_edition.key(),
+                collection_authority_record: None,
+            }
+            .instruction()
+        } else {
+            mpl_token_metadata::instructions::VerifyCollection {
+                metadata: ctx.accounts.edition_metadata.key(),
+                collection_authority: collection_authority.key(),
+                payer: ctx.accounts.authority.key(),
+                collection_mint: ctx.accounts.master_mint.key(),
+                collection: ctx.accounts.master_metadata.key(),
+                collection_master_edition_account: ctx.accounts.master_edition.key(),
+                collection_authority_record: None,
+            }
+            .instruction()
+        };
+        
+        invoke_signed(
+            &instruction,
+            &[
+                ctx.accounts.edition_metadata.to_account_info(),
+                collection_authority,
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.master_mint.to_account_info(),
+                ctx.accounts.master_metadata.to_account_info(),
+                ctx.accounts.master_edition.to_account_info(),
+                ctx.accounts.token_metadata_program.to_account_info(),
+            ],
+            signer,
+        )?;
+        
+        msg!("Verified {} in collection {}", ctx.accounts.edition_mint.key(), ctx.accounts.master_mint.key());
+        
+        Ok(())
+    }
+
+    /// Update pricing (only authority)
+    pub fn update_pricing(
+        ctx: Context<UpdateConfig>,
+        new_regular_price: Option<u64>,
+        new_discounted_price: Option<u64>,
+        new_token_price: Option<u64>,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let changed = new_regular_price.is_some() || new_discounted_price.is_some() || new_token_price.is_some();
+        
+        change_pricing(&mut ctx.accounts.config, changed, |config| {
+            if let Some(price) = new_regular_price {
+                config.mint_price = price;
+                msg!("Updated regular price to: {} lamports", price);
+            }
+            
+            if let Some(price) = new_discounted_price {
+                config.discounted_price = price;
+                msg!("Updated discounted price to: {} lamports", price);
+            }
+            
+            if let Some(price) = new_token_price {
+                config.token_price = price;
+                msg!("Updated token price to: {}", price);
+            }
+        })
+    }
+
+    /// Update the minimum seconds between price changes, 0 disables (only authority)
+    pub fn update_price_cooldown(ctx: Context<UpdateConfig>, cooldown: i64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(cooldown >= 0, ErrorCode::InvalidCooldown);
+        ctx.accounts.config.price_change_cooldown = cooldown;
+        
+        msg!("Updated price change cooldown to: {} seconds", cooldown);
+        
+        Ok(())
+    }
+
+    /// Price regular mints on a bonding curve, base + increment * editions minted so far,
+    /// instead of the flat mint price (only authority). Subject to the price cooldown and
+    /// grace period like `update_pricing`.
+    pub fn update_bonding_curve(
+        ctx: Context<UpdateConfig>,
+        curve_enabled: bool,
+        base_price: u64,
+        price_increment: u64,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        change_pricing(&mut ctx.accounts.config, true, |config| {
+            config.curve_enabled = curve_enabled;
+            config.base_price = base_price;
+            config.price_increment = price_increment;
+            
+            msg!(
+                "Updated bonding curve: enabled {}, base {} lamports, increment {} lamports",
+                curve_enabled,
+                base_price,
+                price_increment
+            );
+        })
+    }
+
+    /// Set how the discounted price is derived (only authority): the flat discounted price,
+    /// or `ratio_bps` of the current regular price, curve included. Subject to the price
+    /// cooldown like `update_pricing`.
+    pub fn update_discount_mode(ctx: Context<UpdateConfig>, mode: DiscountMode, ratio_bps: u16) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(ratio_bps <= 10_000, ErrorCode::InvalidBasisPoints);
+        change_pricing(&mut ctx.accounts.config, true, |config| {
+            config.discount_mode = mode;
+            config.discount_ratio_bps = ratio_bps;
+            
+            match mode {
+                DiscountMode::Fixed => msg!("Discounted price fixed at {} lamports", config.discounted_price),
+                DiscountMode::RatioOfRegular => msg!("Discounted price set to {} bps of the regular price", ratio_bps),
+            }
+        })
+    }
+
+    /// Update the seconds a raised regular price keeps charging the old one, 0 disables
+    /// (only authority)
+    pub fn update_price_grace(ctx: Context<UpdateConfig>, grace_period: i64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(grace_period >= 0, ErrorCode::InvalidCooldown);
+        ctx.accounts.config.price_grace_period = grace_period;
+        
+        msg!("Updated price grace period to: {} seconds", grace_period);
+        
+        Ok(())
+    }
+
+    /// Set the per-wallet limit for holders of a verified collection NFT, 0 applies the
+    /// default limit to everyone (only authority)
+    pub fn update_holder_wallet_limit(ctx: Context<UpdateConfig>, holder_max_per_wallet: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.holder_max_per_wallet = holder_max_per_wallet;
+        
+        msg!("Updated holder per-wallet limit to: {}", holder_max_per_wallet);
+        
+        Ok(())
+    }
+
+    /// Freeze new editions in the minter's wallet until `unlock_ts` (only authority)
+    pub fn update_mint_lock(ctx: Context<UpdateConfig>, freeze_on_mint: bool, unlock_ts: i64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        // Both lock the edition through the token's single delegate slot
+        require!(!freeze_on_mint || config.custodian.is_none(), ErrorCode::InvalidCustodian);
+        config.freeze_on_mint = freeze_on_mint;
+        config.unlock_ts = unlock_ts;
+        
+        msg!("Updated freeze on mint to: {}, unlock at: {}", freeze_on_mint, unlock_ts);
+        
+        Ok(())
+    }
+
+    /// Set the voucher mint whose tokens `mint_by_burning` redeems, None disables (only authority)
+    pub fn update_redeemable_mint(ctx: Context<UpdateConfig>, redeemable_mint: Option<Pubkey>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.redeemable_mint = redeemable_mint;
+        
+        msg!("Updated redeemable mint to: {:?}", redeemable_mint);
+        
+        Ok(())
+    }
+
+    /// Set how many public free mints `mint_free` hands out in total (only authority)
+    pub fn update_free_supply(ctx: Context<UpdateConfig>, free_supply: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.free_supply = free_supply;
+        
+        msg!("Updated free supply to: {}", free_supply);
+        
+        Ok(())
+    }
+
+    /// Set the staking program whose stake accounts unlock `mint_for_stakers`, None disables
+    /// (only authority). Only accounts carrying `stake_discriminator` count as stake accounts.
+    pub fn update_staking(
+        ctx: Context<UpdateConfig>,
+        staking_program: Option<Pubkey>,
+        min_stake: u64,
+        stake_discriminator: [u8; 8],
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        config.staking_program = staking_program;
+        config.min_stake = min_stake;
+        config.stake_discriminator = stake_discriminator;
+        
+        msg!("Updated staking program to: {:?}, minimum stake: {}", staking_program, min_stake);
+        
+        Ok(())
+    }
+
+    /// Update the share of the price paid to referral codes (only authority)
+    pub fn update_referral_rate(ctx: Context<UpdateConfig>, basis_points: u16) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(basis_points <= 10_000, ErrorCode::InvalidBasisPoints);
+        ctx.accounts.config.referral_basis_points = basis_points;
+        
+        msg!("Updated referral rate to: {} bps", basis_points);
+        
+        Ok(())
+    }
+
+    /// Cap how many mints a referral code earns from, 0 is unlimited (only authority).
+    /// Over the cap, mints are rejected when `reject_over_cap` is set, otherwise the cut is skipped.
+    pub fn update_referral_cap(
+        ctx: Context<UpdateConfig>,
+        max_mints: u64,
+        reject_over_cap: bool,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        config.max_referral_mints = max_mints;
+        config.referral_cap_rejects = reject_over_cap;
+        
+        msg!("Updated referral cap to: {} mints (reject: {})", max_mints, reject_over_cap);
+        
+        Ok(())
+    }
+
+    /// Enable or disable mint-and-list and set the listing escrow owner (only authority)
+    pub fn update_auto_list(
+        ctx: Context<UpdateConfig>,
+        enabled: bool,
+        list_escrow: Option<Pubkey>,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        
+        if let Some(escrow) = list_escrow {
+            config.list_escrow = escrow;
+            msg!("Updated listing escrow to: {}", escrow);
+        }
+        
+        require!(
+            !enabled || config.list_escrow != Pubkey::default(),
+            ErrorCode::AutoListDisabled
+        );
+        config.auto_list_enabled = enabled;
+        msg!("Updated mint-and-list to: {}", enabled);
+        
+        Ok(())
+    }
+
+    /// Set or clear the custodian delegated on every new edition token (only authority).
+    /// The custodian becomes a plain SPL delegate of the token: it can transfer or burn the
+    /// edition as well as freeze it, and the holder can revoke the delegation at any time.
+    pub fn update_custodian(ctx: Context<UpdateConfig>, custodian: Option<Pubkey>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(custodian.is_none() || !ctx.accounts.config.freeze_on_mint, ErrorCode::InvalidCustodian);
+        ctx.accounts.config.custodian = custodian;
+        msg!("Updated custodian to: {:?}", custodian);
+        
+        Ok(())
+    }
+
+    /// Update the default edition mutability and immutable-mint policy (only authority)
+    pub fn update_mutability(
+        ctx: Context<UpdateConfig>,
+        is_mutable: Option<bool>,
+        allow_immutable: Option<bool>,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        
+        if let Some(mutable) = is_mutable {
+            config.is_mutable = mutable;
+            msg!("Updated default mutability to: {}", mutable);
+        }
+        
+        if let Some(allow) = allow_immutable {
+            config.allow_immutable = allow;
+            msg!("Updated immutable mint policy to: {}", allow);
+        }
+        
+        // The default itself must satisfy the policy
+        require!(
+            config.is_mutable || config.allow_immutable,
+            ErrorCode::ImmutableMintsForbidden
+        );
+        
+        Ok(())
+    }
+
+    /// Update the minimum SOL balance a minter must hold beyond the price (only authority)
+    pub fn update_min_minter_balance(ctx: Context<UpdateConfig>, min_balance: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        config.min_minter_balance = min_balance;
+        
+        msg!("Updated minimum minter balance to: {} lamports", min_balance);
+        
+        Ok(())
+    }
+
+    /// Emergency stop for every mint path (only authority). Still allowed after finalizing,
+    /// so a finalized drop can be stopped and closed.
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.paused = paused;
+        
+        msg!("Minting paused: {}", paused);
+        
+        Ok(())
+    }
+
+    /// Turn lamport payments on or off for every SOL mint path (only authority). Allowed
+    /// after finalizing, like `set_paused`.
+    pub fn set_sol_mint_enabled(ctx: Context<SetPaused>, enabled: bool) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.sol_mint_enabled = enabled;
+        
+        msg!("SOL mints enabled: {}", enabled);
+        
+        Ok(())
+    }
+
+    /// Turn SPL token payments on or off for `mint_with_token` (only authority). Allowed
+    /// after finalizing, like `set_paused`.
+    pub fn set_token_mint_enabled(ctx: Context<SetPaused>, enabled: bool) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.token_mint_enabled = enabled;
+        
+        msg!("Token mints enabled: {}", enabled);
+        
+        Ok(())
+    }
+
+    /// Update the lamport and token halves of a split payment (only authority, 0 for either
+    /// disables `mint_split_payment`)
+    pub fn update_split_pricing(ctx: Context<UpdateConfig>, sol_price: u64, token_price: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        config.split_sol_price = sol_price;
+        config.split_token_price = token_price;
+        
+        msg!("Updated split pricing to {} lamports + {} tokens", sol_price, token_price);
+        
+        Ok(())
+    }
+
+    /// Update how many editions one wallet may mint (only authority, 0 is unlimited)
+    pub fn update_wallet_limit(ctx: Context<UpdateConfig>, max_per_wallet: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.max_per_wallet = max_per_wallet;
+        
+        msg!("Updated per-wallet limit to: {}", max_per_wallet);
+        
+        Ok(())
+    }
+
+    /// Update how many mint transactions one wallet may send per UTC day (only authority,
+    /// 0 is unlimited)
+    pub fn update_daily_limit(ctx: Context<UpdateConfig>, max_mints_per_day: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.max_mints_per_day = max_mints_per_day;
+        
+        msg!("Updated daily mint limit to: {}", max_mints_per_day);
+        
+        Ok(())
+    }
+
+    /// Reserve the top `count` edition numbers of `max_supply` for `mint_edition_at`
+    /// (only authority, 0 clears). The range is fixed once a reserved number is claimed.
+    pub fn update_reserved_editions(ctx: Context<UpdateConfig>, count: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        require!(config.reserved_minted == 0, ErrorCode::InvalidReservedEditions);
+        require!(
+            count == 0
+                || (config.max_supply > 0
+                    && count <= config.max_supply.saturating_sub(sequential_minted(config))),
+            ErrorCode::InvalidReservedEditions
+        );
+        config.reserved_editions = count;
+        
+        msg!("Reserved the top {} edition numbers", count);
+        
+        Ok(())
+    }
+
+    /// Set or clear the collection whose verified holders may call mint_gated, along with
+    /// the update authority every gate NFT must carry (only authority)
+    pub fn update_gate(
+        ctx: Context<UpdateConfig>,
+        gate_collection: Option<Pubkey>,
+        gate_update_authority: Pubkey,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        config.gate_collection = gate_collection;
+        config.gate_update_authority = gate_update_authority;
+        
+        msg!("Updated gate collection to: {:?}", gate_collection);
+        
+        Ok(())
+    }
+
+    /// Set the lowest lamport price a SOL mint may settle at after discounts, 0 disables (only authority)
+    pub fn update_min_effective_price(ctx: Context<UpdateConfig>, min_effective_price: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.min_effective_price = min_effective_price;
+        
+        msg!("Updated minimum effective price to: {}", min_effective_price);
+        
+        Ok(())
+    }
+
+    /// Set the base name, symbol and URI used for new editions (only authority)
+    pub fn update_metadata_config(
+        ctx: Context<UpdateConfig>,
+        base_name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require_valid_metadata_config(&base_name, &symbol, &uri)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.base_name = base_name;
+        config.symbol = symbol;
+        config.uri = uri;
+        
+        msg!("Updated metadata to {} ({}) at {}", config.base_name, config.symbol, config.uri);
+        
+        Ok(())
+    }
+
+    /// Set the URI prefix editions reveal to through `reveal_with_secret` (only authority)
+    pub fn update_reveal_base_uri(ctx: Context<UpdateConfig>, reveal_base_uri: String) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(
+            reveal_base_uri.len() <= MAX_REVEAL_BASE_URI_LEN,
+            ErrorCode::InvalidMetadataConfig
+        );
+        
+        msg!("Updated reveal base URI to: {}", reveal_base_uri);
+        ctx.accounts.config.reveal_base_uri = reveal_base_uri;
+        
+        Ok(())
+    }
+
+    /// Set the default royalty and the wallet it is paid to for new editions (only authority).
+    /// The recipient must be the collection's primary creator so editions and collection
+    /// pay royalties to the same wallet.
+    pub fn update_royalties(
+        ctx: Context<UpdateRoyalties>,
+        royalty_basis_points: u16,
+        royalty_recipient: Pubkey,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(royalty_basis_points <= 10_000, ErrorCode::InvalidBasisPoints);
+        require_royalty_within_ceiling(&ctx.accounts.config, royalty_basis_points)?;
+        
+        let collection = read_edition_metadata(
+            &ctx.accounts.master_metadata,
+            &ctx.accounts.config.master_mint,
+        )?;
+        require_primary_creator(&collection, royalty_recipient)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.royalty_basis_points = royalty_basis_points;
+        config.royalty_recipient = royalty_recipient;
+        require_creator_shares(&edition_creators(config, &config.authority))?;
+        
+        msg!("Updated royalties to {} bps paid to {}", royalty_basis_points, royalty_recipient);
+        
+        Ok(())
+    }
+
+    /// Cap the seller fee `update_royalties`, `update_rarity_tiers` and `set_collection_royalty`
+    /// may set (only authority). It can't drop below the royalties already configured.
+    pub fn update_max_royalty(ctx: Context<UpdateConfig>, max_royalty_bps: u16) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(max_royalty_bps <= 10_000, ErrorCode::InvalidBasisPoints);
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.royalty_basis_points <= max_royalty_bps
+                && config
+                    .rarity_tiers
+                    .iter()
+                    .all(|tier| tier.seller_fee_basis_points <= max_royalty_bps),
+            ErrorCode::RoyaltyExceedsCeiling
+        );
+        config.max_royalty_bps = max_royalty_bps;
+        
+        msg!("Updated royalty ceiling to: {} bps", max_royalty_bps);
+        
+        Ok(())
+    }
+
+    /// Route secondary royalties to a split program's wallet, e.g. a Hydra fanout (only
+    /// authority). New editions give it the full share; omitting the wallet restores the
+    /// default. The wallet must be a funded, non-program account other than the recipient.
+    pub fn update_royalty_split(ctx: Context<UpdateRoyaltySplit>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let royalty_split_wallet = ctx.accounts.royalty_split_wallet.as_ref().map(|wallet| wallet.key());
+        if let Some(wallet) = ctx.accounts.royalty_split_wallet.as_ref() {
+            require!(
+                wallet.key() != Pubkey::default()
+                    && wallet.key() != ctx.accounts.config.royalty_recipient
+                    && wallet.lamports() > 0
+                    && !wallet.executable,
+                ErrorCode::InvalidRoyaltySplit
+            );
+        }
+        ctx.accounts.config.royalty_split_wallet = royalty_split_wallet;
+        require_creator_shares(&edition_creators(&ctx.accounts.config, &ctx.accounts.config.authority))?;
+        
+        msg!("Updated royalty split wallet to: {:?}", royalty_split_wallet);
+        
+        Ok(())
+    }
+
+    /// Raise the supply cap (only authority); it can never drop below what is already minted.
+    /// A sized collection bounds it as in `initialize`.
+    pub fn update_max_supply(ctx: Context<UpdateMaxSupply>, max_supply: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let collection = read_edition_metadata(
+            &ctx.accounts.master_metadata,
+            &ctx.accounts.config.master_mint,
+        )?;
+        let max_supply = collection_supply_cap(&collection, max_supply)?;
+        let config = &mut ctx.accounts.config;
+        require!(!config.collection_finalized, ErrorCode::CollectionFinalized);
+        require!(
+            max_supply == 0 || max_supply >= config.total_minted,
+            ErrorCode::InvalidMaxSupply
+        );
+        // Moving the cap moves the reserved range with it
+        if config.reserved_editions > 0 {
+            let sequential_cap = max_supply.checked_sub(config.reserved_editions);
+            require!(
+                config.reserved_minted == 0
+                    && sequential_cap.is_some_and(|cap| cap >= sequential_minted(config)),
+                ErrorCode::InvalidReservedEditions
+            );
+        }
+        config.max_supply = max_supply;
+        
+        msg!("Updated max supply to: {}", max_supply);
+        
+        Ok(())
+    }
+
+    /// Update the slot at which minting stops for good (only authority, 0 disables)
+    pub fn update_end_slot(ctx: Context<UpdateConfig>, end_slot: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        config.end_slot = end_slot;
+        
+        msg!("Updated end slot to: {}", end_slot);
+        
+        Ok(())
+    }
+
+    /// Set the Merkle root of wallets allowed to mint at the discounted price, all zeroes
+    /// opens the discount to everyone (only authority)
+    pub fn set_allowlist_root(ctx: Context<UpdateConfig>, merkle_root: [u8; 32]) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.merkle_root = merkle_root;
+        
+        msg!("Updated allowlist root");
+        
+        Ok(())
+    }
+
+    /// Verify the minter's allowlist proof once and record it for the current root, so
+    /// `mint_discounted` can check the record instead of a proof. A new root invalidates it.
+    pub fn register_allowlist(ctx: Context<RegisterAllowlist>, proof: Vec<[u8; 32]>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        require_allowlisted(config, &ctx.accounts.minter.key(), &proof)?;
+        
+        let status = &mut ctx.accounts.allowlist_status;
+        status.minter = ctx.accounts.minter.key();
+        status.merkle_root = config.merkle_root;
+        status.bump = ctx.bumps.allowlist_status;
+        
+        msg!("Registered {} on the allowlist", status.minter);
+        
+        Ok(())
+    }
+
+    /// Peg the regular price to USD cents through a Pyth SOL/USD price update account,
+    /// 0 cents keeps the lamport price (only authority). Updates must carry `price_feed_id`
+    /// and a confidence interval within `max_confidence_bps` of the price. Subject to the
+    /// price cooldown and grace period like `update_pricing`.
+    pub fn update_usd_pricing(
+        ctx: Context<UpdateConfig>,
+        usd_price_cents: u64,
+        price_feed: Pubkey,
+        max_price_age: u64,
+        price_feed_id: [u8; 32],
+        max_confidence_bps: u16,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(max_confidence_bps <= 10_000, ErrorCode::InvalidBasisPoints);
+        change_pricing(&mut ctx.accounts.config, true, |config| {
+            config.usd_price_cents = usd_price_cents;
+            config.price_feed = price_feed;
+            config.max_price_age = max_price_age;
+            config.price_feed_id = price_feed_id;
+            config.max_confidence_bps = max_confidence_bps;
+            
+            msg!("Updated USD price to {} cents via feed {}", usd_price_cents, price_feed);
+        })
+    }
+
+    /// Start vesting withdrawals linearly from `vesting_start` over `vesting_duration`
+    /// seconds, 0 duration lifts the cap (only authority)
+    pub fn update_withdrawal_vesting(
+        ctx: Context<UpdateConfig>,
+        vesting_start: i64,
+        vesting_duration: i64,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(vesting_duration >= 0, ErrorCode::InvalidWithdrawalVesting);
+        
+        let config = &mut ctx.accounts.config;
+        config.vesting_start = vesting_start;
+        config.vesting_duration = vesting_duration;
+        
+        msg!("Updated withdrawal vesting to {}s from {}", vesting_duration, vesting_start);
+        
+        Ok(())
+    }
+
+    /// Set the unix-time window in which minting is open, 0 leaves a bound open (only authority)
+    pub fn update_mint_window(ctx: Context<UpdateConfig>, mint_start_ts: i64, mint_end_ts: i64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require_valid_mint_window(mint_start_ts, mint_end_ts)?;
+        
+        let config = &mut ctx.accounts.config;
+        config.mint_start_ts = mint_start_ts;
+        config.mint_end_ts = mint_end_ts;
+        
+        msg!("Updated mint window to {} - {}", mint_start_ts, mint_end_ts);
+        
+        Ok(())
+    }
+
+    /// Allow or forbid manual corrections to the mint counter (only authority)
+    pub fn update_admin_adjust(ctx: Context<UpdateConfig>, allowed: bool) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.allow_admin_adjust = allowed;
+        
+        msg!("Updated admin adjustments to: {}", allowed);
+        
+        Ok(())
+    }
+
+    /// Reset total_minted to a count verified off-chain (only authority, when allowed). The
+    /// count must cover every edition number already handed out and fit the supply cap.
+    pub fn reconcile_supply(ctx: Context<UpdateConfig>, verified_count: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        require!(config.allow_admin_adjust, ErrorCode::AdminAdjustDisabled);
+        // Every reserved edition minted stays counted, and the sequential count can neither
+        // reuse a number already handed out nor run into the reserved range
+        let sequential = verified_count
+            .checked_sub(config.reserved_minted)
+            .ok_or(ErrorCode::InvalidReconciledCount)?;
+        require!(
+            sequential >= config.highest_sequential_edition,
+            ErrorCode::InvalidReconciledCount
+        );
+        if config.max_supply > 0 {
+            require!(sequential <= sequential_cap(config)?, ErrorCode::InvalidReconciledCount);
+        }
+        
+        let previous = config.total_minted;
+        config.total_minted = verified_count;
+        
+        emit!(SupplyReconciled {
+            previous,
+            total_minted: verified_count,
+            delta: verified_count as i128 - previous as i128,
+        });
+        msg!("Reconciled total minted from {} to {}", previous, verified_count);
+        
+        Ok(())
+    }
+
+    /// Replace the rarity tier royalty table; tiers must ascend by edition (only authority)
+    pub fn update_rarity_tiers(ctx: Context<UpdateConfig>, tiers: Vec<RarityTier>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(tiers.len() <= MAX_RARITY_TIERS, ErrorCode::InvalidRarityTiers);
+        require!(
+            tiers.windows(2).all(|pair| pair[0].max_edition < pair[1].max_edition),
+            ErrorCode::InvalidRarityTiers
+        );
+        require!(
+            tiers.iter().all(|tier| tier.seller_fee_basis_points <= 10_000),
+            ErrorCode::InvalidBasisPoints
+        );
+        for tier in &tiers {
+            require_royalty_within_ceiling(&ctx.accounts.config, tier.seller_fee_basis_points)?;
+        }
+        
+        msg!("Updated rarity tiers: {} configured", tiers.len());
+        ctx.accounts.config.rarity_tiers = tiers;
+        
+        Ok(())
+    }
+
+    /// Replace the bulk discount table for `mint_batch`; tiers must ascend by size with
+    /// growing discounts, and start at two editions (only authority)
+    pub fn update_bulk_tiers(ctx: Context<UpdateConfig>, tiers: Vec<BulkTier>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(tiers.len() <= MAX_BULK_TIERS, ErrorCode::InvalidBulkTiers);
+        require!(
+            tiers.iter().all(|tier| tier.min_quantity >= 2)
+                && tiers
+                    .windows(2)
+                    .all(|pair| pair[0].min_quantity < pair[1].min_quantity && pair[0].discount_bps < pair[1].discount_bps),
+            ErrorCode::InvalidBulkTiers
+        );
+        require!(
+            tiers.iter().all(|tier| tier.discount_bps <= 10_000),
+            ErrorCode::InvalidBasisPoints
+        );
+        
+        msg!("Updated bulk tiers: {} configured", tiers.len());
+        ctx.accounts.config.bulk_tiers = tiers;
+        
+        Ok(())
+    }
+
+    /// Set the mint counts that emit a `Milestone` event: every `interval` mints (0 disables)
+    /// plus an ascending list of explicit counts (only authority)
+    pub fn update_milestones(ctx: Context<UpdateConfig>, interval: u64, milestones: Vec<u64>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(milestones.len() <= MAX_MILESTONES, ErrorCode::InvalidMilestones);
+        require!(
+            !milestones.contains(&0)
+                && milestones.windows(2).all(|pair| pair[0] < pair[1]),
+            ErrorCode::InvalidMilestones
+        );
+        
+        msg!("Updated milestones: every {} mints, {} explicit", interval, milestones.len());
+        let config = &mut ctx.accounts.config;
+        config.milestone_interval = interval;
+        config.milestones = milestones;
+        
+        Ok(())
+    }
+
+    /// Create an active gas subsidy campaign that sponsors account rent for mints by the
+    /// wallets its scope admits (only authority)
+    pub fn create_campaign(
+        ctx: Context<CreateCampaign>,
+        campaign_id: u64,
+        scope: CampaignScope,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let campaign = &mut ctx.accounts.campaign;
+        campaign.id = campaign_id;
+        campaign.total_sponsored = 0;
+        campaign.active = true;
+        campaign.scope = scope;
+        campaign.bump = ctx.bumps.campaign;
+        
+        msg!("Campaign {} created", campaign_id);
+        
+        Ok(())
+    }
+
+    /// Start or stop a campaign's sponsorship; mints naming an inactive one fail (only authority)
+    pub fn set_campaign_active(ctx: Context<UpdateCampaign>, active: bool) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.campaign.active = active;
+        
+        msg!("Campaign {} active: {}", ctx.accounts.campaign.id, active);
+        
+        Ok(())
+    }
+
+    /// Prefund a campaign with SOL used to sponsor mints
+    pub fn fund_campaign(ctx: Context<FundCampaign>, amount: u64) -> Result<()> {
+        invoke(
+            &system_instruction::transfer(
+                ctx.accounts.funder.key,
+                &ctx.accounts.campaign.key(),
+                amount,
+            ),
+            &[
+                ctx.accounts.funder.to_account_info(),
+                ctx.accounts.campaign.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+        
+        msg!("Campaign {} funded with {} lamports", ctx.accounts.campaign.id, amount);
+        
+        Ok(())
+    }
+
+    /// Create a Bubblegum tree owned by the config PDA for compressed mints (only authority)
+    pub fn create_compressed_tree(
+        ctx: Context<CreateCompressedTree>,
+        max_depth: u32,
+        max_buffer_size: u32,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let seeds = &[
+            b"config".as_ref(),
+            &[ctx.bumps.config],
+        ];
+        let signer = &[&seeds[..]];
+        
+        invoke_signed(
+            &mpl_bubblegum::instructions::CreateTreeConfig {
+                tree_config: ctx.accounts.tree_config.key(),
+                merkle_tree: ctx.accounts.merkle_tree.key(),
+                payer: ctx.accounts.authority.key(),
+                tree_creator: ctx.accounts.config.key(),
+                log_wrapper: ctx.accounts.log_wrapper.key(),
+                compression_program: ctx.accounts.compression_program.key(),
+                system_program: ctx.accounts.system_program.key(),
+            }
+            .instruction(mpl_bubblegum::instructions::CreateTreeConfigInstructionArgs {
+                max_depth,
+                max_buffer_size,
+                public: Some(false),
+            }),
+            &[
+                ctx.accounts.tree_config.to_account_info(),
+                ctx.accounts.merkle_tree.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.config.to_account_info(),
+                ctx.accounts.log_wrapper.to_account_info(),
+                ctx.accounts.compression_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.bubblegum_program.to_account_info(),
+            ],
+            signer,
+        )?;
+        
+        ctx.accounts.config.merkle_tree = ctx.accounts.merkle_tree.key();
+        msg!("Compressed minting enabled with tree: {}", ctx.accounts.merkle_tree.key());
+        
+        Ok(())
+    }
+
+    /// Mint a compressed edition into the configured Merkle tree (regular price)
+    pub fn mint_compressed(ctx: Context<MintCompressed>) -> Result<()> {
+        let config_info = ctx.accounts.config.to_account_info();
+        let config = &mut ctx.accounts.config;
+        require!(
+            config.merkle_tree != Pubkey::default(),
+            ErrorCode::CompressedMintingDisabled
+        );
+        
+        let price = begin_mint(
+            config,
+            MintCharge {
+                minter: ctx.accounts.minter.to_account_info(),
+                payment_vault: ctx.accounts.payment_vault.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                minter_record: &mut ctx.accounts.minter_record,
+                price_feed: ctx.accounts.price_feed.as_ref(),
+                holder_token_account: ctx.accounts.holder_token_account.as_ref(),
+                holder_metadata: ctx.accounts.holder_metadata.as_ref(),
+                referral: None,
+            },
+            &Payment::Sol { discounted: false },
+            1,
+        )?;
+        let edition_number = next_edition_number(config, None)?;
+        
+        // The new leaf's asset id is derived from the tree's mint count before this mint
+        let tree_config = mpl_bubblegum::accounts::TreeConfig::from_bytes(&ctx.accounts.tree_config.try_borrow_data()?)
+            .map_err(|_| error!(ErrorCode::InvalidMetadataAccount))?;
+        let asset_id = mpl_bubblegum::utils::get_asset_id(&ctx.accounts.merkle_tree.key(), tree_config.num_minted);
+        
+        msg!("Minting COMPRESSED edition #{} for {}", edition_number, ctx.accounts.minter.key());
+        emit_edition_minted(&config.master_mint, ctx.accounts.minter.key(), asset_id, edition_number, price, false);
+        
+        let seeds = &[
+            b"config".as_ref(),
+            &[ctx.bumps.config],
+        ];
+        let signer = &[&seeds[..]];
+        
+        invoke_signed(
+            &mpl_bubblegum::instructions::MintV1 {
+                tree_config: ctx.accounts.tree_config.key(),
+                leaf_owner: ctx.accounts.minter.key(),
+                leaf_delegate: ctx.accounts.minter.key(),
+                merkle_tree: ctx.accounts.merkle_tree.key(),
+                payer: ctx.accounts.minter.key(),
+                tree_creator_or_delegate: config_info.key(),
+                log_wrapper: ctx.accounts.log_wrapper.key(),
+                compression_program: ctx.accounts.compression_program.key(),
+                system_program: ctx.accounts.system_program.key(),
+            }
+            .instruction(mpl_bubblegum::instructions::MintV1InstructionArgs {
+                metadata: mpl_bubblegum::types::MetadataArgs {
+                    name: edition_name(config, edition_number)?,
+                    symbol: config.symbol.clone(),
+                    uri: config.uri.clone(),
+                    seller_fee_basis_points: edition_seller_fee(config, edition_number),
+                    primary_sale_happened: false,
+                    is_mutable: config.is_mutable,
+                    edition_nonce: None,
+                    token_standard: Some(mpl_bubblegum::types::TokenStandard::NonFungible),
+                    collection: Some(mpl_bubblegum::types::Collection {
+                        verified: false,
+                        key: config.master_mint,
+                    }),
+                    uses: None,
+                    token_program_version: mpl_bubblegum::types::TokenProgramVersion::Original,
+                    creators: edition_creators(config, &ctx.accounts.minter.key())
+                        .into_iter()
+                        .map(|creator| mpl_bubblegum::types::Creator {
+                            address: creator.address,
+                            verified: creator.verified,
+                            share: creator.share,
+                        })
+                        .collect(),
+                },
+            }),
+            &[
+                ctx.accounts.tree_config.to_account_info(),
+                ctx.accounts.minter.to_account_info(),
+                ctx.accounts.merkle_tree.to_account_info(),
+                config_info,
+                ctx.accounts.log_wrapper.to_account_info(),
+                ctx.accounts.compression_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                ctx.accounts.bubblegum_program.to_account_info(),
+            ],
+            signer,
+        )?;
+        
+        msg!("Compressed NFT successfully minted!");
+        
+        Ok(())
+    }
+
+    /// Update the auto-sweep destination and vault threshold, 0 disables (only authority)
+    pub fn update_auto_sweep(
+        ctx: Context<UpdateConfig>,
+        creator_wallet: Option<Pubkey>,
+        threshold: Option<u64>,
+    ) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        
+        if let Some(wallet) = creator_wallet {
+            config.creator_wallet = wallet;
+            msg!("Updated creator wallet to: {}", wallet);
+        }
+        
+        if let Some(threshold) = threshold {
+            // The vault must stay rent exempt after a sweep
+            require!(
+                threshold == 0 || threshold >= Rent::get()?.minimum_balance(0),
+                ErrorCode::InvalidSweepThreshold
+            );
+            config.auto_sweep_threshold = threshold;
+            msg!("Updated auto-sweep threshold to: {} lamports", threshold);
+        }
+        
+        Ok(())
+    }
+
+    /// Sweep vault funds above the threshold to the creator wallet, up to the vested
+    /// amount while withdrawal vesting is active (anyone)
+    pub fn auto_sweep(ctx: Context<AutoSweep>) -> Result<()> {
+        let threshold = ctx.accounts.config.auto_sweep_threshold;
+        require!(threshold > 0, ErrorCode::AutoSweepDisabled);
+        
+        let balance = ctx.accounts.payment_vault.lamports();
+        require!(balance > threshold, ErrorCode::VaultBelowSweepThreshold);
+        // Sweeps draw on the same vested allowance as withdrawals
+        let vested = vested_withdrawable(&ctx.accounts.config, &ctx.accounts.payment_vault)?;
+        let amount = (balance - threshold).min(vested);
+        require!(amount > 0, ErrorCode::ExceedsVested);
+        let config = &mut ctx.accounts.config;
+        config.total_withdrawn = config.total_withdrawn.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        
+        let seeds = &[
+            b"payment_vault".as_ref(),
+            &[ctx.bumps.payment_vault],
+        ];
+        let signer = &[&seeds[..]];
+        
+        invoke_signed(
+            &system_instruction::transfer(
+                ctx.accounts.payment_vault.key,
+                ctx.accounts.creator_wallet.key,
+                amount,
+            ),
+            &[
+                ctx.accounts.payment_vault.to_account_info(),
+                ctx.accounts.creator_wallet.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            signer,
+        )?;
+        
+        msg!("Swept {} lamports to creator wallet", amount);
+        
+        Ok(())
+    }
+
+    /// Irreversibly freeze all drop parameters; only minting and withdrawals remain (only authority)
+    pub fn finalize_drop(ctx: Context<UpdateConfig>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.finalized = true;
+        
+        msg!("Drop finalized, configuration is now read-only");
+        
+        Ok(())
+    }
+
+    /// Close out a sold-out drop once (only authority): checks the minted count fits a sized
+    /// collection, makes the collection metadata immutable, locks the supply cap and emits
+    /// `CollectionComplete` with the final stats.
+    pub fn finalize_collection(ctx: Context<FinalizeCollection>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &ctx.accounts.config;
+        require!(!config.collection_finalized, ErrorCode::CollectionFinalized);
+        require!(
+            config.max_supply > 0 && config.total_minted >= config.max_supply,
+            ErrorCode::NotSoldOut
+        );
+        
+        let collection = read_edition_metadata(&ctx.accounts.master_metadata, &config.master_mint)?;
+        let collection_size = match collection.collection_details {
+            Some(CollectionDetails::V1 { size }) => {
+                require!(config.total_minted <= size, ErrorCode::SupplyExceedsCollection);
+                size
+            }
+            _ => 0,
+        };
+        
+        // The collection may still belong to the authority wallet or already to the config PDA
+        let update_authority = if collection.update_authority == config.key() {
+            config.to_account_info()
+        } else {
+            ctx.accounts.authority.to_account_info()
+        };
+        let seeds = &[
+            b"config".as_ref(),
+            &[ctx.bumps.config],
+        ];
+        let signer = &[&seeds[..]];
+        
+        invoke_signed(
+            &mpl_token_metadata::instructions::UpdateMetadataAccountV2 {
+                metadata: ctx.accounts.master_metadata.key(),
+                update_authority: update_authority.key(),
+            }
+            .instruction(mpl_token_metadata::instructions::UpdateMetadataAccountV2InstructionArgs {
+                data: None,
+                new_update_authority: None,
+                primary_sale_happened: None,
+                is_mutable: Some(false),
+            }),
+            &[
+                ctx.accounts.master_metadata.to_account_info(),
+                update_authority,
+            ],
+            signer,
+        )?;
+        
+        let config = &mut ctx.accounts.config;
+        config.collection_finalized = true;
+        emit!(CollectionComplete {
+            master_mint: config.master_mint,
+            total_minted: config.total_minted,
+            total_burned: config.total_burned,
+            max_supply: config.max_supply,
+            collection_size,
+        });
+        
+        msg!("Collection {} complete at {} editions", config.master_mint, config.total_minted);
+        
+        Ok(())
+    }
+
+    /// Propose a new authority, who takes over once they accept (only authority). Allowed
+    /// after finalizing so a compromised key can still be rotated.
+    pub fn propose_authority(ctx: Context<TransferAuthority>, new_authority: Pubkey) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.pending_authority = Some(new_authority);
+        
+        msg!("Proposed authority {}", new_authority);
+        
+        Ok(())
+    }
+
+    /// Withdraw a pending authority proposal (only authority)
+    pub fn cancel_authority_transfer(ctx: Context<TransferAuthority>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        ctx.accounts.config.pending_authority = None;
+        
+        msg!("Authority transfer cancelled");
+        
+        Ok(())
+    }
+
+    /// Promote the pending authority; it must sign
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let config = &mut ctx.accounts.config;
+        config.authority = ctx.accounts.pending_authority.key();
+        config.pending_authority = None;
+        
+        msg!("Authority transferred to {}", config.authority);
+        
+        Ok(())
+    }
+
+    /// Grow a config created by the first program release to the current layout (only
+    /// authority). Fields that release hardcoded take its values: the "AMMo Founder" name,
+    /// "FAMMo" symbol and URI, mutable editions and a 500 bps royalty paid to the
+    /// collection's primary creator. Other new fields start zeroed, which leaves each
+    /// feature disabled until the authority sets it.
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        let config = &ctx.accounts.config;
+        let master_mint = {
+            let data = config.try_borrow_data()?;
+            require!(
+                data.len() >= 8 + 32 && data[..8] == <Config as anchor_lang::Discriminator>::DISCRIMINATOR,
+                anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+            );
+            require_keys_eq!(
+                Pubkey::new_from_array(data[8..40].try_into().unwrap()),
+                ctx.accounts.authority.key(),
+                ErrorCode::Unauthorized
+            );
+            
+            let space = 8 + Config::INIT_SPACE;
+            require!(data.len() < space, ErrorCode::ConfigAlreadyMigrated);
+            require!(data.len() == FIRST_RELEASE_CONFIG_LEN, ErrorCode::UnknownConfigLayout);
+            Pubkey::new_from_array(data[40..72].try_into().unwrap())
+        };
+        
+        let collection = read_edition_metadata(&ctx.accounts.master_metadata, &master_mint)?;
+        let royalty_recipient = collection
+            .creators
+            .as_ref()
+            .and_then(|creators| creators.first())
+            .map(|creator| creator.address)
+            .ok_or(ErrorCode::MissingCollectionCreator)?;
+        let max_supply = collection_supply_cap(&collection, 0)?;
+        
+        let space = 8 + Config::INIT_SPACE;
+        let previous = config.data_len();
+        
+        let shortfall = Rent::get()?.minimum_balance(space).saturating_sub(config.lamports());
+        if shortfall > 0 {
+            invoke(
+                &system_instruction::transfer(&ctx.accounts.authority.key(), &config.key(), shortfall),
+                &[
+                    ctx.accounts.authority.to_account_info(),
+                    config.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        config.realloc(space, true)?;
+        migrate_first_release(&mut config.try_borrow_mut_data()?, royalty_recipient, max_supply)?;
+        
+        msg!("Config migrated from {} to {} bytes", previous, space);
+        
+        Ok(())
+    }
+
+    /// Close the config and return its rent to the authority once the drop is paused and
+    /// the payment and token vaults have been fully withdrawn (only authority)
+    pub fn close_config(ctx: Context<CloseConfig>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        
+        // The token vault only exists once a token payment has been made
+        let token_vault = &ctx.accounts.token_vault;
+        if !token_vault.data_is_empty() {
+            let vault = TokenAccount::try_deserialize(&mut &token_vault.try_borrow_data()?[..])?;
+            require!(vault.amount == 0, ErrorCode::VaultNotEmpty);
+        }
+        
+        msg!("Config closed, rent returned to {}", ctx.accounts.authority.key());
+        
+        Ok(())
+    }
+
+    /// Close the caller's minter record and reclaim its rent. Only allowed once the drop
+    /// is finalized and minting has stopped, so active per-wallet limits cannot be reset
+    /// by closing the record.
+    pub fn close_minter_record(ctx: Context<CloseMinterRecord>) -> Result<()> {
+        require!(minting_closed(&ctx.accounts.config)?, ErrorCode::MintStillOpen);
+        
+        msg!(
+            "Closed minter record for {} after {} mints",
+            ctx.accounts.minter.key(),
+            ctx.accounts.minter_record.count
+        );
+        
+        Ok(())
+    }
+
+    /// Withdraw SPL payments from the token vault (only authority)
+    pub fn withdraw_tokens(ctx: Context<WithdrawTokens>, amount: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(
+            amount <= ctx.accounts.token_vault.amount,
+            ErrorCode::InsufficientVaultBalance
+        );
+        let seeds = &[
+            b"config".as_ref(),
+            &[ctx.bumps.config],
+        ];
+        let signer = &[&seeds[..]];
+        
+        anchor_spl::token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                anchor_spl::token::Transfer {
+                    from: ctx.accounts.token_vault.to_account_info(),
+                    to: ctx.accounts.destination.to_account_info(),
+                    authority: ctx.accounts.config.to_account_info(),
+                },
+                signer,
+            ),
+            amount,
+        )?;
+        
+        msg!("Withdrawn {} tokens to {}", amount, ctx.accounts.destination.key());
+        
+        Ok(())
+    }
+
+    /// Withdraw collected funds (only authority)
+    pub fn withdraw(ctx: Context<Withdraw>, amount: u64) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        require!(
+            amount <= ctx.accounts.payment_vault.lamports(),
+            ErrorCode::InsufficientVaultBalance
+        );
+        let withdrawable = vested_withdrawable(&ctx.accounts.config, &ctx.accounts.payment_vault)?;
+        require!(amount <= withdrawable, ErrorCode::ExceedsVested);
+        let config = &mut ctx.accounts.config;
+        config.total_withdrawn = config.total_withdrawn.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        
+        let seeds = &[
+            b"payment_vault".as_ref(),
+            &[ctx.bumps.payment_vault],
+        ];
+        let signer = &[&seeds[..]];
+        
+        invoke_signed(
+            &system_instruction::transfer(
+                ctx.accounts.payment_vault.key,
+                ctx.accounts.authority.key,
+                amount,
+            ),
+            &[
+                ctx.accounts.payment_vault.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            signer,
+        )?;
+        
+        msg!("Withdrawn {} lamports to authority", amount);
+        emit!(FundsWithdrawn {
+            authority: ctx.accounts.authority.key(),
+            amount,
+        });
+        
+        Ok(())
+    }
+
+    /// Withdraw everything above the vault's rent-exempt minimum, or only the vested part
+    /// while withdrawal vesting is active (only authority)
+    pub fn withdraw_all(ctx: Context<Withdraw>) -> Result<()> {
+        require_canonical_config(&ctx.accounts.config.key())?;
+        let vault = &ctx.accounts.payment_vault;
+        let rent_floor = Rent::get()?.minimum_balance(vault.data_len());
+        let available = vault.lamports().saturating_sub(rent_floor);
+        require!(available > 0, ErrorCode::NothingToWithdraw);
+        
+        let amount = available.min(vested_withdrawable(&ctx.accounts.config, vault)?);
+        require!(amount > 0, ErrorCode::ExceedsVested);
+        let config = &mut ctx.accounts.config;
+        config.total_withdrawn = config.total_withdrawn.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        
+        let seeds = &[
+            b"payment_vault".as_ref(),
+            &[ctx.bumps.payment_vault],
+        ];
+        let signer = &[&seeds[..]];
+        
+        invoke_signed(
+            &system_instruction::transfer(
+                ctx.accounts.payment_vault.key,
+                ctx.accounts.authority.key,
+                amount,
+            ),
+            &[
+                ctx.accounts.payment_vault.to_account_info(),
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+            signer,
+        )?;
+        
+        msg!("Withdrawn {} of {} lamports above the vault's rent minimum", amount, available);
+        emit!(FundsWithdrawn {
+            authority: ctx.accounts.authority.key(),
+            amount,
+        });
+        
+        Ok(())
+    }
+}
+
+/// The edition metadata and master edition must be the Metaplex PDAs of the new mint,
+/// so a wrong account fails here instead of deep inside the Token Metadata CPI
+fn require_edition_pdas(edition_mint: &AccountInfo, edition_metadata: &AccountInfo, edition: &AccountInfo) -> Result<()> {
+    let mint = edition_mint.key();
+    require_keys_eq!(
+        edition_metadata.key(),
+        Metadata::find_pda(&mint).0,
+        ErrorCode::InvalidMetadataAccount
+    );
+    require_keys_eq!(
+        edition.key(),
+        MasterEdition::find_pda(&mint).0,
+        ErrorCode::InvalidMetadataAccount
+    );
+    Ok(())
+}
+
+/// Remember the first edition's mint as the drop's provenance anchor; later mints leave it
+/// untouched. Compressed editions have no mint account and are not recorded.
+fn record_genesis_mint(config: &mut Config, mint: Pubkey) {
+    if config.genesis_mint.is_none() {
+        config.genesis_mint = Some(mint);
+        msg!("Genesis mint recorded: {}", mint);
+    }
+}
+
+/// Lamports the authority may still withdraw: the vested share of everything collected
+/// (current vault surplus plus past withdrawals) minus what was already withdrawn
+fn vested_withdrawable(config: &Config, vault: &AccountInfo) -> Result<u64> {
+    if config.vesting_duration == 0 {
+        return Ok(u64::MAX);
+    }
+    let rent_floor = Rent::get()?.minimum_balance(vault.data_len());
+    let collected = vault.lamports().saturating_sub(rent_floor) as u128 + config.total_withdrawn as u128;
+    let elapsed = Clock::get()?
+        .unix_timestamp
+        .saturating_sub(config.vesting_start)
+        .clamp(0, config.vesting_duration);
+    let vested = collected * elapsed as u128 / config.vesting_duration as u128;
+    Ok((vested as u64).saturating_sub(config.total_withdrawn))
+}
+
+/// A sized collection caps the supply at its size, which an uncapped supply of 0 also takes
+fn collection_supply_cap(collection: &Metadata, max_supply: u64) -> Result<u64> {
+    match collection.collection_details {
+        Some(CollectionDetails::V1 { size }) => {
+            require!(max_supply <= size, ErrorCode::SupplyExceedsCollection);
+            Ok(if max_supply == 0 { size } else { max_supply })
+        }
+        _ => Ok(max_supply),
+    }
+}
+
+/// Royalties must be paid to the collection's primary (first) creator
+fn require_primary_creator(collection: &Metadata, royalty_recipient: Pubkey) -> Result<()> {
+    let primary_creator = collection
+        .creators
+        .as_ref()
+        .and_then(|creators| creators.first())
+        .map(|creator| creator.address);
+    require!(
+        primary_creator == Some(royalty_recipient),
+        ErrorCode::MissingCollectionCreator
+    );
+    Ok(())
+}
+
+/// Fill a first release config, already grown and zero-extended to the current size, with
+/// the values that release hardcoded so minting works unchanged after the migration
+fn migrate_first_release(data: &mut [u8], royalty_recipient: Pubkey, max_supply: u64) -> Result<()> {
+    let mut config = Config::try_deserialize(&mut &data[..])?;
+    config.is_mutable = true;
+    config.allow_immutable = true;
+    config.creator_wallet = config.authority;
+    config.base_name = FIRST_RELEASE_BASE_NAME.to_string();
+    config.symbol = FIRST_RELEASE_SYMBOL.to_string();
+    config.uri = FIRST_RELEASE_URI.to_string();
+    config.royalty_basis_points = FIRST_RELEASE_ROYALTY_BPS;
+    config.royalty_recipient = royalty_recipient;
+    config.max_supply = max_supply;
+    config.sol_mint_enabled = true;
+    config.token_mint_enabled = true;
+    config.max_royalty_bps = 10_000;
+    config.highest_sequential_edition = config.total_minted;
+    config.try_serialize(&mut &mut data[..])
+}
+
+/// Summarize a minter record that may not exist yet, zeroed when it doesn't
+fn read_minter_summary(record_info: &AccountInfo, wallet: Pubkey) -> Result<MinterSummary> {
+    if record_info.data_is_empty() {
+        return Ok(MinterSummary {
+            minter: wallet,
+            count: 0,
+            discounted_count: 0,
+            last_mint_ts: 0,
+            lifetime_count: 0,
+        });
+    }
+    let record = MinterRecord::try_deserialize(&mut &record_info.try_borrow_data()?[..])?;
+    Ok(MinterSummary {
+        minter: wallet,
+        count: record.count,
+        discounted_count: record.discounted_count,
+        last_mint_ts: record.last_mint_ts,
+        lifetime_count: record.lifetime_count,
+    })
+}
+
+/// Defense in depth: authority paths re-derive the config PDA instead of trusting constraints alone
+fn require_canonical_config(config: &Pubkey) -> Result<()> {
+    let (expected, _) = Pubkey::find_program_address(&[b"config"], &crate::ID);
+    require_keys_eq!(*config, expected, ErrorCode::InvalidConfig);
+    Ok(())
+}
+
+/// Deserialize an edition's metadata after checking it is the Metaplex PDA for `mint`
+fn read_edition_metadata(metadata: &AccountInfo, mint: &Pubkey) -> Result<Metadata> {
+    require_keys_eq!(metadata.key(), Metadata::find_pda(mint).0, ErrorCode::InvalidMetadataAccount);
+    require_keys_eq!(*metadata.owner, TOKEN_METADATA_PROGRAM_ID, ErrorCode::InvalidMetadataAccount);
+    
+    Metadata::safe_deserialize(&metadata.try_borrow_data()?)
+        .map_err(|_| error!(ErrorCode::InvalidMetadataAccount))
+}
+
+/// Rebuild the updatable data of a metadata account, dropping Metaplex's padding
+fn metadata_data(metadata: &Metadata) -> DataV2 {
+    DataV2 {
+        name: metadata.name.trim_end_matches('\0').to_string(),
+        symbol: metadata.symbol.trim_end_matches('\0').to_string(),
+        uri: metadata.uri.trim_end_matches('\0').to_string(),
+        seller_fee_basis_points: metadata.seller_fee_basis_points,
+        creators: metadata.creators.clone(),
+        collection: metadata.collection.clone(),
+        uses: metadata.uses.clone(),
+    }
+}
+
+/// Require that the instruction before this one is an Ed25519 verification of `message`
+/// signed by `signer`, with the key and message embedded in that same instruction
+fn require_relay_authorization(instructions: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<()> {
+    let current = load_current_index_checked(instructions)?;
+    require!(current > 0, ErrorCode::InvalidRelayAuthorization);
+    let ed25519_ix = load_instruction_at_checked(current as usize - 1, instructions)?;
+    require_keys_eq!(ed25519_ix.program_id, ed25519_program::ID, ErrorCode::InvalidRelayAuthorization);
+    
+    // Layout: count, padding, then seven u16 offsets per signature
+    let data = &ed25519_ix.data;
+    require!(data.len() >= 16 && data[0] == 1, ErrorCode::InvalidRelayAuthorization);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let (signature_ix, pubkey_offset, pubkey_ix) = (read_u16(4), read_u16(6) as usize, read_u16(8));
+    let (message_offset, message_size, message_ix) =
+        (read_u16(10) as usize, read_u16(12) as usize, read_u16(14));
+    require!(
+        [signature_ix, pubkey_ix, message_ix].iter().all(|&index| index == u16::MAX),
+        ErrorCode::InvalidRelayAuthorization
+    );
+    
+    require!(
+        data.get(pubkey_offset..pubkey_offset + 32) == Some(signer.as_ref())
+            && data.get(message_offset..message_offset + message_size) == Some(message),
+        ErrorCode::InvalidRelayAuthorization
+    );
+    Ok(())
+}
+
+/// sha256 of an edition's name, symbol and URI, each prefixed by its little-endian u32
+/// length so no characters can move between fields unnoticed
+fn edition_content_hash(name: &str, symbol: &str, uri: &str) -> [u8; 32] {
+    let mut fields: Vec<&[u8]> = Vec::new();
+    let lengths = [name.len() as u32, symbol.len() as u32, uri.len() as u32].map(u32::to_le_bytes);
+    for (length, field) in lengths.iter().zip([name, symbol, uri]) {
+        fields.push(length);
+        fields.push(field.as_bytes());
+    }
+    anchor_lang::solana_program::hash::hashv(&fields).to_bytes()
+}
+
+/// Deterministic identifier for an edition: sha256 of the master mint and the
+/// little-endian edition number
+fn edition_serial_hash(master_mint: &Pubkey, edition_number: u64) -> [u8; 32] {
+    anchor_lang::solana_program::hash::hashv(&[master_mint.as_ref(), &edition_number.to_le_bytes()])
+        .to_bytes()
+}
+
+/// Replace an edition's URI through the config PDA, its update authority
+fn set_edition_uri<'info>(
+    config: &AccountInfo<'info>,
+    config_bump: u8,
+    edition_metadata: &AccountInfo<'info>,
+    metadata: &Metadata,
+    uri: String,
+) -> Result<()> {
+    let mut data = metadata_data(metadata);
+    data.uri = uri;
+    
+    let seeds = &[
+        b"config".as_ref(),
+        &[config_bump],
+    ];
+    let signer = &[&seeds[..]];
+    
+    invoke_signed(
+        &mpl_token_metadata::instructions::UpdateMetadataAccountV2 {
+            metadata: edition_metadata.key(),
+            update_authority: config.key(),
+        }
+        .instruction(mpl_token_metadata::instructions::UpdateMetadataAccountV2InstructionArgs {
+            data: Some(data),
+            new_update_authority: None,
+            primary_sale_happened: None,
+            is_mutable: None,
+        }),
+        &[
+            edition_metadata.clone(),
+            config.clone(),
+        ],
+        signer,
+    )?;
+    
+    Ok(())
+}
+
+/// Royalty of the first rarity tier covering the edition, or the default
+fn edition_seller_fee(config: &Config, edition_number: u64) -> u16 {
+    config
+        .rarity_tiers
+        .iter()
+        .find(|tier| edition_number <= tier.max_edition)
+        .map_or(config.royalty_basis_points, |tier| tier.seller_fee_basis_points)
+}
+
+fn require_royalty_within_ceiling(config: &Config, basis_points: u16) -> Result<()> {
+    require!(basis_points <= config.max_royalty_bps, ErrorCode::RoyaltyExceedsCeiling);
+    Ok(())
+}
+
+/// Metaplex rejects creator lists whose shares don't add up to 100
+fn require_creator_shares(creators: &[Creator]) -> Result<()> {
+    let total: u16 = creators.iter().map(|creator| u16::from(creator.share)).sum();
+    require!(total == 100, ErrorCode::InvalidCreatorShares);
+    Ok(())
+}
+
+/// The royalty recipient takes the full share; the minter stays on as a verified creator
+/// with no share, since it signs the metadata creation. A configured split wallet takes the
+/// full share instead and leads the list, with the recipient kept on as the primary creator
+/// at no share. The split wallet stays unverified: verifying a creator needs that creator's
+/// own signature, which it never gives here.
+fn edition_creators(config: &Config, minter: &Pubkey) -> Vec<Creator> {
+    let mut creators = Vec::with_capacity(3);
+    let recipient_share = match config.royalty_split_wallet {
+        Some(split_wallet) => {
+            creators.push(Creator {
+                address: split_wallet,
+                verified: false,
+                share: 100,
+            });
+            0
+        }
+        None => 100,
+    };
+    if config.royalty_recipient == *minter {
+        creators.push(Creator {
+            address: *minter,
+            verified: true,
+            share: recipient_share,
+        });
+        return creators;
+    }
+    creators.push(Creator {
+        address: config.royalty_recipient,
+        verified: false,
+        share: recipient_share,
+    });
+    creators.push(Creator {
+        address: *minter,
+        verified: true,
+        share: 0,
+    });
+    creators
+}
+
+/// Every edition counts against the supply cap, whichever path mints it (0 is unlimited)
+fn require_supply_available(config: &Config) -> Result<()> {
+    require!(
+        config.max_supply == 0 || config.total_minted < config.max_supply,
+        ErrorCode::MaxSupplyReached
+    );
+    Ok(())
+}
+
+/// Reject mints while paused, outside the mint window or once the configured end slot
+/// is reached (0 disables each bound)
+fn require_mint_open(config: &Config) -> Result<()> {
+    require!(!config.paused, ErrorCode::MintingPaused);
+    let clock = Clock::get()?;
+    if config.mint_start_ts > 0 {
+        require!(clock.unix_timestamp >= config.mint_start_ts, ErrorCode::MintNotStarted);
+    }
+    if config.mint_end_ts > 0 {
+        require!(clock.unix_timestamp < config.mint_end_ts, ErrorCode::MintEnded);
+    }
+    if config.end_slot > 0 {
+        require!(clock.slot < config.end_slot, ErrorCode::MintEnded);
+    }
+    Ok(())
+}
+
+/// True once no further editions can be minted: paused, sold out, or past the mint
+/// window's end or the end slot
+fn minting_closed(config: &Config) -> Result<bool> {
+    let clock = Clock::get()?;
+    Ok(config.paused
+        || (config.max_supply > 0 && config.total_minted >= config.max_supply)
+        || (config.mint_end_ts > 0 && clock.unix_timestamp >= config.mint_end_ts)
+        || (config.end_slot > 0 && clock.slot >= config.end_slot))
+}
+
+/// Base units in one whole voucher token
+fn voucher_amount(decimals: u8) -> Result<u64> {
+    10u64.checked_pow(decimals.into()).ok_or(error!(ErrorCode::InvalidVoucher))
+}
+
+/// List price of the edition minted after `minted` others: base + increment * minted on
+/// the bonding curve when enabled, otherwise the flat price
+fn list_price(config: &Config, minted: u64) -> Result<u64> {
+    if !config.curve_enabled {
+        return Ok(config.mint_price);
+    }
+    config
+        .price_increment
+        .checked_mul(minted)
+        .and_then(|rise| rise.checked_add(config.base_price))
+        .ok_or(error!(ErrorCode::Overflow))
+}
+
+/// Regular lamport price of the edition minted after `minted` others: its list price, or
+/// the lower pre-raise price while the grace period runs
+fn regular_price_at(config: &Config, minted: u64, now: i64) -> Result<u64> {
+    let price = list_price(config, minted)?;
+    if now < config.price_locked_until {
+        return Ok(price.min(config.previous_mint_price));
+    }
+    Ok(price)
+}
+
+/// Regular lamport price of the next edition
+fn regular_price(config: &Config) -> Result<u64> {
+    regular_price_at(config, config.total_minted, Clock::get()?.unix_timestamp)
+}
+
+/// Lamports for the next `quantity` regular-price editions, each priced at its own count
+fn regular_batch_cost(config: &Config, quantity: u64) -> Result<u64> {
+    let now = Clock::get()?.unix_timestamp;
+    (0..quantity).try_fold(0u64, |total, offset| {
+        let minted = config.total_minted.checked_add(offset).ok_or(ErrorCode::Overflow)?;
+        total
+            .checked_add(regular_price_at(config, minted, now)?)
+            .ok_or(error!(ErrorCode::Overflow))
+    })
+}
+
+/// Discounted share of a regular price in `RatioOfRegular` mode
+fn ratio_of_regular(config: &Config, regular: u64) -> u64 {
+    (regular as u128 * config.discount_ratio_bps as u128 / 10_000) as u64
+}
+
+/// Regular-price batch total less the largest bulk tier discount `quantity` reaches
+fn bulk_discounted(config: &Config, quantity: u64, total: u64) -> u64 {
+    let discount_bps = config
+        .bulk_tiers
+        .iter()
+        .filter(|tier| quantity >= tier.min_quantity)
+        .map(|tier| tier.discount_bps)
+        .max()
+        .unwrap_or(0);
+    total - (total as u128 * discount_bps as u128 / 10_000) as u64
+}
+
+/// Shared path of every pricing setter: enforce the change cooldown, apply `update`, keep
+/// charging the lower pre-change price through the grace period when the regular lamport
+/// or USD price rose, and emit PricingUpdated when a listed price moved. `changed` is
+/// false for a no-op update, which leaves the cooldown running as it was.
+fn change_pricing(config: &mut Config, changed: bool, update: impl FnOnce(&mut Config)) -> Result<()> {
+    // Protect buyers from whipsawing prices
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now >= config.last_price_change_ts.saturating_add(config.price_change_cooldown),
+        ErrorCode::PriceChangeTooSoon
+    );
+    if changed {
+        config.last_price_change_ts = now;
+    }
+    
+    let list_before = list_price(config, config.total_minted)?;
+    let usd_before = config.usd_price_cents;
+    let discounted_before = listed_discounted_price(config, list_before);
+    let token_before = config.token_price;
+    update(config);
+    let list_after = list_price(config, config.total_minted)?;
+    let usd_after = config.usd_price_cents;
+    let discounted_after = listed_discounted_price(config, list_after);
+    
+    // Buyers already in flight keep the old price through the grace period
+    let raised = list_after > list_before || (usd_before > 0 && usd_after > usd_before);
+    if raised && config.price_grace_period > 0 {
+        config.previous_mint_price = list_before.min(list_after);
+        config.previous_usd_price_cents = if usd_before > 0 { usd_before.min(usd_after) } else { usd_after };
+        config.price_locked_until = now.saturating_add(config.price_grace_period);
+    }
+    
+    if list_after == list_before
+        && discounted_after == discounted_before
+        && usd_after == usd_before
+        && config.token_price == token_before
+    {
+        return Ok(());
+    }
+    emit!(PricingUpdated {
+        regular_price: list_after,
+        discounted_price: discounted_after,
+    });
+    
+    Ok(())
+}
+
+/// Discounted lamport price listed alongside a regular list price
+fn listed_discounted_price(config: &Config, list: u64) -> u64 {
+    match config.discount_mode {
+        DiscountMode::Fixed => config.discounted_price,
+        DiscountMode::RatioOfRegular => ratio_of_regular(config, list),
+    }
+}
+
+/// Convert the USD-pegged price to lamports using the configured Pyth SOL/USD
+/// PriceUpdateV2 account, rejecting partially verified, stale or non-positive prices,
+/// updates for another feed id and prices with too wide a confidence interval
+fn usd_price_in_lamports(config: &Config, price_feed: Option<&AccountInfo>) -> Result<u64> {
+    let feed = price_feed
+        .filter(|info| info.key() == config.price_feed)
+        .ok_or(ErrorCode::StaleOracle)?;
+    require_keys_eq!(*feed.owner, PYTH_RECEIVER_ID, ErrorCode::StaleOracle);
+    
+    // discriminator, write authority, verification level (Full is the single byte 1),
+    // then feed id, price, conf, exponent and publish time
+    let data = feed.try_borrow_data()?;
+    require!(
+        data.len() >= 101 && data[..8] == PRICE_UPDATE_V2_DISCRIMINATOR && data[40] == 1,
+        ErrorCode::StaleOracle
+    );
+    // Slices are in bounds after the length check above
+    require!(data[41..73] == config.price_feed_id, ErrorCode::StaleOracle);
+    let price = i64::from_le_bytes(data[73..81].try_into().unwrap());
+    let conf = u64::from_le_bytes(data[81..89].try_into().unwrap());
+    let exponent = i32::from_le_bytes(data[89..93].try_into().unwrap());
+    let publish_time = i64::from_le_bytes(data[93..101].try_into().unwrap());
+    
+    let age = Clock::get()?.unix_timestamp.saturating_sub(publish_time);
+    require!(price > 0 && age <= config.max_price_age as i64, ErrorCode::StaleOracle);
+    require!((-18..=0).contains(&exponent), ErrorCode::StaleOracle);
+    require!(
+        conf as u128 * 10_000 <= price as u128 * config.max_confidence_bps as u128,
+        ErrorCode::StaleOracle
+    );
+    
+    // A raised USD price keeps charging the old one through the grace period; 0 means the
+    // peg was off before the lock and there is no older USD price
+    let locked = Clock::get()?.unix_timestamp < config.price_locked_until;
+    let cents = if locked && config.previous_usd_price_cents > 0 {
+        config.usd_price_cents.min(config.previous_usd_price_cents)
+    } else {
+        config.usd_price_cents
+    };
+    
+    // lamports = cents / 100 * LAMPORTS_PER_SOL / (price * 10^exponent)
+    let numerator = (cents as u128)
+        .checked_mul(10u128.pow(exponent.unsigned_abs()))
+        .and_then(|value| value.checked_mul(10_000_000))
+        .ok_or(ErrorCode::StaleOracle)?;
+    u64::try_from(numerator / price as u128).map_err(|_| error!(ErrorCode::StaleOracle))
+}
+
+/// Check a stake account owned by the configured staking program belongs to the minter
+/// and holds at least `min_stake`. Stake accounts start with the configured 8-byte
+/// discriminator, so other accounts of that program are refused, followed by the
+/// staker's key and the staked amount.
+fn require_staking(config: &Config, minter: &Pubkey, stake_account: &AccountInfo) -> Result<()> {
+    let staking_program = config.staking_program.ok_or(ErrorCode::NotStaking)?;
+    require_keys_eq!(*stake_account.owner, staking_program, ErrorCode::NotStaking);
+    
+    let data = stake_account.try_borrow_data()?;
+    require!(data.len() >= 48, ErrorCode::NotStaking);
+    // Slices are in bounds after the length check above
+    require!(data[..8] == config.stake_discriminator, ErrorCode::NotStaking);
+    let staker = Pubkey::new_from_array(data[8..40].try_into().unwrap());
+    let amount = u64::from_le_bytes(data[40..48].try_into().unwrap());
+    require_keys_eq!(staker, *minter, ErrorCode::NotStaking);
+    require!(amount >= config.min_stake, ErrorCode::NotStaking);
+    
+    msg!("Stake of {} accepted", amount);
+    Ok(())
+}
+
+/// Walk a Merkle proof from keccak(minter) up to the allowlist root, hashing each pair in
+/// sorted order so proofs carry no left/right flags
+fn require_allowlisted(config: &Config, minter: &Pubkey, proof: &[[u8; 32]]) -> Result<()> {
+    if config.merkle_root == [0; 32] {
+        return Ok(());
+    }
+    let mut node = keccak::hash(minter.as_ref()).to_bytes();
+    for sibling in proof {
+        node = if node <= *sibling {
+            keccak::hashv(&[&node, sibling]).to_bytes()
+        } else {
+            keccak::hashv(&[sibling, &node]).to_bytes()
+        };
+    }
+    require!(node == config.merkle_root, ErrorCode::NotAllowlisted);
+    Ok(())
+}
+
+/// Both bounds are optional, but when set the window must be non-empty
+fn require_valid_mint_window(mint_start_ts: i64, mint_end_ts: i64) -> Result<()> {
+    require!(
+        mint_start_ts == 0 || mint_end_ts == 0 || mint_start_ts < mint_end_ts,
+        ErrorCode::InvalidMintWindow
+    );
+    Ok(())
+}
+
+/// Metadata name of an edition, rejecting numbers too long for the 32-byte Metaplex name
+fn edition_name(config: &Config, edition_number: u64) -> Result<String> {
+    let name = format!("{} #{}", config.base_name, edition_number);
+    require!(name.len() <= MAX_NAME_LENGTH, ErrorCode::InvalidEditionNumber);
+    Ok(name)
+}
+
+/// Edition names append " #<edition>" to the base name, so it must leave room for the suffix
+fn require_valid_metadata_config(base_name: &str, symbol: &str, uri: &str) -> Result<()> {
+    require!(
+        !base_name.is_empty()
+            && base_name.len() <= MAX_BASE_NAME_LEN
+            && symbol.len() <= MAX_SYMBOL_LENGTH
+            && !uri.is_empty()
+            && uri.len() <= MAX_URI_LENGTH,
+        ErrorCode::InvalidMetadataConfig
+    );
+    Ok(())
+}
+
+/// Weak sybil deterrent: the minter must keep a balance beyond the price (0 disables)
+fn require_minter_balance(config: &Config, minter: &AccountInfo, price: u64) -> Result<()> {
+    if config.min_minter_balance > 0 {
+        let required = price
+            .checked_add(config.min_minter_balance)
+            .ok_or(ErrorCode::Overflow)?;
+        require!(minter.lamports() >= required, ErrorCode::MinterBalanceTooLow);
+    }
+    Ok(())
+}
+
+/// How a mint is paid for
+enum Payment<'info> {
+    /// Lamports into the payment vault, at the discounted price when set
+    Sol { discounted: bool },
+    /// Payment-mint tokens from the minter's token account into the token vault
+    Token { from: AccountInfo<'info>, vault: AccountInfo<'info> },
+    /// The split prices, lamports into the payment vault and tokens into the token vault
+    Split { from: AccountInfo<'info>, vault: AccountInfo<'info> },
+    /// Nothing, counted against the config's free supply by the caller
+    Free,
+    /// One whole voucher token burned from the minter's token account
+    Voucher { from: AccountInfo<'info>, mint: AccountInfo<'info>, decimals: u8 },
+}
+
+/// Editions numbered sequentially so far, the last sequential edition number
+fn sequential_minted(config: &Config) -> u64 {
+    config.total_minted.saturating_sub(config.reserved_minted)
+}
+
+/// Highest edition number sequential mints may reach under a supply cap
+fn sequential_cap(config: &Config) -> Result<u64> {
+    config
+        .max_supply
+        .checked_sub(config.reserved_editions)
+        .ok_or(error!(ErrorCode::Overflow))
+}
+
+/// Edition numbers only `mint_edition_at` may claim, empty unless some are reserved
+fn reserved_range(config: &Config) -> Result<std::ops::RangeInclusive<u64>> {
+    let start = sequential_cap(config)?.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    Ok(start..=config.max_supply)
+}
+
+/// Count one more edition and return its number: `requested` from the reserved range,
+/// otherwise the next sequential number, which stops below the reserved range
+fn next_edition_number(config: &mut Config, requested: Option<u64>) -> Result<u64> {
+    let edition_number = match requested {
+        Some(edition_number) => {
+            require!(reserved_range(config)?.contains(&edition_number), ErrorCode::InvalidEditionNumber);
+            config.reserved_minted = config.reserved_minted.checked_add(1).ok_or(ErrorCode::Overflow)?;
+            edition_number
+        }
+        None => {
+            let edition_number = sequential_minted(config).checked_add(1).ok_or(ErrorCode::Overflow)?;
+            require!(
+                config.max_supply == 0 || edition_number <= sequential_cap(config)?,
+                ErrorCode::MaxSupplyReached
+            );
+            config.highest_sequential_edition = config.highest_sequential_edition.max(edition_number);
+            edition_number
+        }
+    };
+    increment_total_minted(config)?;
+    Ok(edition_number)
+}
+
+/// Per-wallet limit for this mint: minters showing a verified master-collection NFT get
+/// `holder_max_per_wallet` when it is set, everyone else `max_per_wallet`
+fn wallet_limit(
+    config: &Config,
+    holder_token_account: Option<&Account<TokenAccount>>,
+    holder_metadata: Option<&AccountInfo>,
+) -> Result<u64> {
+    let (Some(token_account), Some(metadata)) = (holder_token_account, holder_metadata) else {
+        return Ok(config.max_per_wallet);
+    };
+    if config.holder_max_per_wallet == 0 {
+        return Ok(config.max_per_wallet);
+    }
+    
+    require!(token_account.amount >= 1, ErrorCode::NotAHolder);
+    let metadata = read_edition_metadata(metadata, &token_account.mint)?;
+    require!(
+        metadata
+            .collection
+            .is_some_and(|collection| collection.verified && collection.key == config.master_mint),
+        ErrorCode::NotAHolder
+    );
+    Ok(config.holder_max_per_wallet)
+}
+
+/// Count one more edition, announcing it when the new total is a configured milestone.
+/// The edition that sells out a capped supply pauses minting, so later attempts fail fast.
+fn increment_total_minted(config: &mut Config) -> Result<()> {
+    config.total_minted = config.total_minted.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    
+    let total_minted = config.total_minted;
+    let on_interval = config.milestone_interval > 0 && total_minted % config.milestone_interval == 0;
+    if on_interval || config.milestones.contains(&total_minted) {
+        msg!("Milestone reached: {} minted", total_minted);
+        emit!(Milestone {
+            total_minted,
+            max_supply: config.max_supply,
+        });
+    }
+    if config.max_supply > 0 && total_minted == config.max_supply {
+        config.paused = true;
+        msg!("Sold out at {} editions, minting paused", total_minted);
+        emit!(SoldOut { total_minted });
+    }
+    Ok(())
+}
+
+/// Accounts a mint path hands to `begin_mint`
+struct MintCharge<'a, 'info> {
+    minter: AccountInfo<'info>,
+    payment_vault: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    minter_record: &'a mut MinterRecord,
+    price_feed: Option<&'a AccountInfo<'info>>,
+    holder_token_account: Option<&'a Account<'info, TokenAccount>>,
+    holder_metadata: Option<&'a AccountInfo<'info>>,
+    /// Referral code sharing the cut of lamport payments
+    referral: Option<Referral<'a, 'info>>,
+}
+
+/// Referral code a lamport payment shares its cut with: paid straight to `payee` when the
+/// affiliate's wallet is supplied, else accrued on the code's PDA for `claim_referral`
+struct Referral<'a, 'info> {
+    code_account: AccountInfo<'info>,
+    code: &'a mut ReferralCode,
+    payee: Option<AccountInfo<'info>>,
+}
+
+/// Whether `code` has used up its `max_referral_mints`, so the cut is skipped; rejects the
+/// mint instead when `referral_cap_rejects` is set
+fn referral_over_cap(config: &Config, code: &ReferralCode) -> Result<bool> {
+    let over_cap = config.max_referral_mints > 0 && code.mints >= config.max_referral_mints;
+    require!(
+        !(over_cap && config.referral_cap_rejects),
+        ErrorCode::ReferralCapReached
+    );
+    Ok(over_cap)
+}
+
+/// Lamport price of the next edition: the discounted price, else the USD peg when set,
+/// else the regular price
+fn sol_price(config: &Config, price_feed: Option<&AccountInfo>, discounted: bool) -> Result<u64> {
+    if discounted {
+        match config.discount_mode {
+            DiscountMode::Fixed => Ok(config.discounted_price),
+            DiscountMode::RatioOfRegular => {
+                let regular = sol_price(config, price_feed, false)?;
+                Ok(ratio_of_regular(config, regular))
+            }
+        }
+    } else if config.usd_price_cents > 0 {
+        usd_price_in_lamports(config, price_feed)
+    } else {
+        regular_price(config)
+    }
+}
+
+/// Checks and payment every mint path runs before creating `quantity` editions: the mint
+/// window, remaining supply and the minter's wallet limit, recorded on their MinterRecord.
+/// Lamport payments are then priced, held to the `min_effective_price` floor, checked
+/// against the minter balance, split with any referrer and moved into the payment vault.
+/// Returns the first edition's lamport price, 0 for other payments.
+fn begin_mint(config: &Config, accounts: MintCharge, payment: &Payment, quantity: u64) -> Result<u64> {
+    require_mint_open(config)?;
+    match payment {
+        Payment::Sol { .. } => require!(config.sol_mint_enabled, ErrorCode::PaymentMethodDisabled),
+        Payment::Token { .. } => require!(config.token_mint_enabled, ErrorCode::PaymentMethodDisabled),
+        Payment::Split { .. } => require!(
+            config.sol_mint_enabled && config.token_mint_enabled,
+            ErrorCode::PaymentMethodDisabled
+        ),
+        Payment::Free | Payment::Voucher { .. } => {}
+    }
+    if config.max_supply > 0 {
+        let total = config.total_minted.checked_add(quantity).ok_or(ErrorCode::Overflow)?;
+        require!(total <= config.max_supply, ErrorCode::MaxSupplyReached);
+    }
+    
+    let max_per_wallet = wallet_limit(config, accounts.holder_token_account, accounts.holder_metadata)?;
+    let record = accounts.minter_record;
+    let count = record.count.checked_add(quantity).ok_or(ErrorCode::Overflow)?;
+    require!(
+        max_per_wallet == 0 || count <= max_per_wallet,
+        ErrorCode::WalletLimitReached
+    );
+    record.minter = accounts.minter.key();
+    record.count = count;
+    if let Payment::Sol { discounted: true } = payment {
+        record.discounted_count = record.discounted_count.checked_add(quantity).ok_or(ErrorCode::Overflow)?;
+    }
+    let now = Clock::get()?.unix_timestamp;
+    record_daily_mint(record, config.max_mints_per_day, now)?;
+    record.last_mint_ts = now;
+    record.lifetime_count = record.lifetime_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    
+    let Payment::Sol { discounted } = *payment else {
+        return Ok(0);
+    };
+    // On the bonding curve the first edition of a batch is the cheapest
+    let price = sol_price(config, accounts.price_feed, discounted)?;
+    let total = if discounted {
+        price.checked_mul(quantity).ok_or(ErrorCode::Overflow)?
+    } else if config.usd_price_cents > 0 {
+        bulk_discounted(config, quantity, price.checked_mul(quantity).ok_or(ErrorCode::Overflow)?)
+    } else {
+        bulk_discounted(config, quantity, regular_batch_cost(config, quantity)?)
+    };
+    // Lamport floor on whatever price remains after discounts, against dust mints
+    require!(price >= config.min_effective_price, ErrorCode::PriceTooLow);
+    require_minter_balance(config, &accounts.minter, total)?;
+    
+    // The referral cut comes out of the payment before the vault takes its share
+    let cut = match accounts.referral {
+        Some(referral) if !referral_over_cap(config, referral.code)? => {
+            let cut = (total as u128 * config.referral_basis_points as u128 / 10_000) as u64;
+            let to = referral.payee.as_ref().unwrap_or(&referral.code_account);
+            if cut > 0 {
+                invoke(
+                    &system_instruction::transfer(&accounts.minter.key(), &to.key(), cut),
+                    &[
+                        accounts.minter.clone(),
+                        to.clone(),
+                        accounts.system_program.clone(),
+                    ],
+                )?;
+            }
+            let code = referral.code;
+            if referral.payee.is_none() {
+                code.unclaimed = code.unclaimed.checked_add(cut).ok_or(ErrorCode::Overflow)?;
+            }
+            code.total_earned = code.total_earned.checked_add(cut).ok_or(ErrorCode::Overflow)?;
+            code.mints = code.mints.checked_add(quantity).ok_or(ErrorCode::Overflow)?;
+            msg!("Referral code {} credited {} lamports", code.code, cut);
+            cut
+        }
+        Some(referral) => {
+            msg!("Referral code {} reached its cap, no cut credited", referral.code.code);
+            0
+        }
+        None => 0,
+    };
+    // The floor holds for what the vault keeps, not just the list price
+    let vault_share = total - cut;
+    let floor = config.min_effective_price.checked_mul(quantity).ok_or(ErrorCode::Overflow)?;
+    require!(vault_share >= floor, ErrorCode::PriceTooLow);
+    
+    pay_vault(&accounts.minter, &accounts.payment_vault, &accounts.system_program, vault_share)?;
+    msg!("Payment of {} lamports received for {} editions", total, quantity);
+    
+    Ok(price)
+}
+
+/// Move `lamports` from the minter into the payment vault
+fn pay_vault<'info>(
+    minter: &AccountInfo<'info>,
+    payment_vault: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    lamports: u64,
+) -> Result<()> {
+    let vault_before = payment_vault.lamports();
+    invoke(
+        &system_instruction::transfer(&minter.key(), &payment_vault.key(), lamports),
+        &[
+            minter.clone(),
+            payment_vault.clone(),
+            system_program.clone(),
+        ],
+    )?;
+    // Defense in depth: only count the mint once the vault holds exactly its share more
+    require!(
+        vault_before.checked_add(lamports) == Some(payment_vault.lamports()),
+        ErrorCode::PaymentNotReceived
+    );
+    Ok(())
+}
+
+/// Move `amount` payment-mint tokens from the minter's token account into the token vault
+fn pay_token_vault<'info>(
+    token_program: AccountInfo<'info>,
+    minter: AccountInfo<'info>,
+    from: &AccountInfo<'info>,
+    vault: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    let vault_before = anchor_spl::token::accessor::amount(vault)?;
+    anchor_spl::token::transfer(
+        CpiContext::new(
+            token_program,
+            anchor_spl::token::Transfer {
+                from: from.clone(),
+                to: vault.clone(),
+                authority: minter,
+            },
+        ),
+        amount,
+    )?;
+    
+    require!(
+        vault_before.checked_add(amount) == Some(anchor_spl::token::accessor::amount(vault)?),
+        ErrorCode::PaymentNotReceived
+    );
+    Ok(())
+}
+
+/// Count a mint transaction against the wallet's daily throttle, restarting the count on
+/// a new UTC day (0 is unlimited)
+fn record_daily_mint(record: &mut MinterRecord, max_mints_per_day: u64, now: i64) -> Result<()> {
+    let day = now.div_euclid(86_400);
+    if record.day != day {
+        record.day = day;
+        record.daily_count = 0;
+    }
+    let daily_count = record.daily_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+    require!(
+        max_mints_per_day == 0 || daily_count <= max_mints_per_day,
+        ErrorCode::DailyLimitReached
+    );
+    record.daily_count = daily_count;
+    Ok(())
+}
+
+/// Announce a minted edition; `series` is the master whose numbering it belongs to
+fn emit_edition_minted(series: &Pubkey, minter: Pubkey, mint: Pubkey, edition_number: u64, price: u64, discounted: bool) {
+    emit!(EditionMinted {
+        minter,
+        mint,
+        edition_number,
+        price,
+        discounted,
+        serial_hash: edition_serial_hash(series, edition_number),
+    });
+}
+
+/// Internal helper function to mint NFT with price selection, returning the price charged.
+/// The edition goes to `recipient` when given, otherwise to the minter.
+fn mint_nft_internal<'info>(
+    accounts: &mut MintEdition<'info>,
+    payment: Payment<'info>,
+    mutable: Option<bool>,
+    requested_edition: Option<u64>,
+    recipient: Option<AccountInfo<'info>>,
+    referral_code: Option<&mut Account<'info, ReferralCode>>,
+) -> Result<u64> {
+    require_edition_pdas(&accounts.edition_mint, &accounts.edition_metadata, &accounts.edition)?;
+    let config = &mut accounts.config;
+    
+    // Per-mint mutability overrides the config default, subject to policy
+    let is_mutable = mutable.unwrap_or(config.is_mutable);
+    require!(
+        is_mutable || config.allow_immutable,
+        ErrorCode::ImmutableMintsForbidden
+    );
+    
+    // A referral is the affiliate's wallet with the code it owns, or a code alone accruing
+    // on its PDA; either way it must not belong to the minter or recipient
+    let referral = match (&accounts.referrer, accounts.referrer_code.as_mut(), referral_code) {
+        (Some(referrer), Some(code), None) => {
+            require!(code.owner == referrer.key(), ErrorCode::InvalidReferrer);
+            let code_account = code.to_account_info();
+            Some(Referral { code_account, code, payee: Some(referrer.to_account_info()) })
+        }
+        (None, None, Some(code)) => {
+            let code_account = code.to_account_info();
+            Some(Referral { code_account, code, payee: None })
+        }
+        (None, None, None) => None,
+        (_, _, Some(_)) => return err!(ErrorCode::ReferrerWithReferralCode),
+        _ => return err!(ErrorCode::InvalidReferrer),
+    };
+    if let Some(referral) = &referral {
+        let recipient_key = recipient.as_ref().map_or(accounts.minter.key(), |recipient| recipient.key());
+        require!(
+            referral.code.owner != accounts.minter.key() && referral.code.owner != recipient_key,
+            ErrorCode::InvalidReferrer
+        );
+    }
+    
+    let sol_price = begin_mint(
+        config,
+        MintCharge {
+            minter: accounts.minter.to_account_info(),
+            payment_vault: accounts.payment_vault.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            minter_record: &mut accounts.minter_record,
+            price_feed: accounts.price_feed.as_ref(),
+            holder_token_account: accounts.holder_token_account.as_ref(),
+            holder_metadata: accounts.holder_metadata.as_ref(),
+            referral,
+        },
+        &payment,
+        1,
+    )?;
+    let (price, is_discounted) = match payment {
+        Payment::Sol { discounted } => (sol_price, discounted),
+        Payment::Token { .. } => (config.token_price, false),
+        Payment::Split { .. } => (config.split_sol_price, false),
+        Payment::Free | Payment::Voucher { .. } => (0, false),
+    };
+    
+    match &payment {
+        Payment::Sol { .. } => {}
+        Payment::Token { from, vault } => {
+            require_minter_balance(config, &accounts.minter, 0)?;
+            pay_token_vault(
+                accounts.token_program.to_account_info(),
+                accounts.minter.to_account_info(),
+                from,
+                vault,
+                price,
+            )?;
+        }
+        // Both halves move in this instruction, so either failing reverts the other
+        Payment::Split { from, vault } => {
+            require_minter_balance(config, &accounts.minter, price)?;
+            pay_vault(
+                &accounts.minter.to_account_info(),
+                &accounts.payment_vault.to_account_info(),
+                &accounts.system_program.to_account_info(),
+                price,
+            )?;
+            pay_token_vault(
+                accounts.token_program.to_account_info(),
+                accounts.minter.to_account_info(),
+                from,
+                vault,
+                config.split_token_price,
+            )?;
+        }
+        Payment::Free => require_minter_balance(config, &accounts.minter, 0)?,
+        Payment::Voucher { from, mint, decimals } => {
+            require_minter_balance(config, &accounts.minter, 0)?;
+            
+            invoke(
+                &spl_token::instruction::burn_checked(
+                    &anchor_spl::token::ID,
+                    from.key,
+                    mint.key,
+                    accounts.minter.key,
+                    &[],
+                    voucher_amount(*decimals)?,
+                    *decimals,
+                )?,
+                &[
+                    from.clone(),
+                    mint.clone(),
+                    accounts.minter.to_account_info(),
+                ],
+            )?;
+        }
+    }
+
+    // Increment edition counter; reserved-slot mints carry their own number
+    let edition_number = next_edition_number(config, requested_edition)?;
+
+    let mint_type = if is_discounted { "DISCOUNTED" } else { "REGULAR" };
+    msg!("Minting {} edition #{} for {}", mint_type, edition_number, accounts.minter.key());
+    match payment {
+        Payment::Sol { .. } => {}
+        Payment::Token { .. } => msg!("Payment of {} {} tokens received", price, config.payment_mint),
+        Payment::Split { .. } => msg!(
+            "Payment of {} lamports and {} {} tokens received",
+            price,
+            config.split_token_price,
+            config.payment_mint
+        ),
+        Payment::Free => msg!("Free mint {} of {}", config.free_minted, config.free_supply),
+        Payment::Voucher { .. } => msg!("Voucher of {:?} burned", config.redeemable_mint),
+    }
+
+    // Track what the minter spends on account rent so a campaign can sponsor it
+    let balance_before_accounts = accounts.minter.lamports();
+
+    create_edition_nft(
+        &EditionAccounts {
+            creator: accounts.minter.to_account_info(),
+            owner: recipient.unwrap_or_else(|| accounts.minter.to_account_info()),
+            edition_mint: accounts.edition_mint.to_account_info(),
+            edition_token_account: accounts.edition_token_account.to_account_info(),
+            edition_metadata: accounts.edition_metadata.to_account_info(),
+            edition: accounts.edition.to_account_info(),
+            token_metadata_program: accounts.token_metadata_program.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            rent: accounts.rent.to_account_info(),
+        },
+        config,
+        edition_number,
+        is_mutable,
+    )?;
+    record_genesis_mint(config, accounts.edition_mint.key());
+
+    // The master edition PDA takes over the freeze authority, so a custodian freezes
+    // through Token Metadata's delegated freeze once it is the token's delegate. SPL Token
+    // has no freeze-only delegation: the custodian may also transfer or burn, and the
+    // holder may revoke it.
+    if let Some(custodian) = config.custodian {
+        let custodian_info = accounts
+            .custodian
+            .as_ref()
+            .filter(|info| info.key() == custodian)
+            .ok_or(ErrorCode::InvalidCustodian)?;
+        anchor_spl::token::approve(
+            CpiContext::new(
+                accounts.token_program.to_account_info(),
+                anchor_spl::token::Approve {
+                    to: accounts.edition_token_account.to_account_info(),
+                    delegate: custodian_info.to_account_info(),
+                    authority: accounts.minter.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+        msg!("Delegated edition to custodian {}", custodian);
+    }
+    
+    // Lock the edition in the minter's wallet until the config's unlock time
+    if config.freeze_on_mint {
+        let config_info = config.to_account_info();
+        let (_, config_bump) = Pubkey::find_program_address(&[b"config"], &crate::ID);
+        anchor_spl::token::approve(
+            CpiContext::new(
+                accounts.token_program.to_account_info(),
+                anchor_spl::token::Approve {
+                    to: accounts.edition_token_account.to_account_info(),
+                    delegate: config_info.clone(),
+                    authority: accounts.minter.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+        set_edition_frozen(
+            &config_info,
+            config_bump,
+            &accounts.edition_token_account.to_account_info(),
+            &accounts.edition,
+            &accounts.edition_mint,
+            &accounts.token_program.to_account_info(),
+            true,
+        )?;
+        msg!("Edition frozen until {}", config.unlock_ts);
+    }
+
+    // Reimburse account rent from the campaign until its balance is exhausted
+    if let Some(campaign) = accounts.campaign.as_mut() {
+        require!(campaign.active, ErrorCode::CampaignInactive);
+        let eligible = match campaign.scope {
+            CampaignScope::Allowlist => {
+                config.merkle_root != [0; 32]
+                    && accounts
+                        .allowlist_status
+                        .as_ref()
+                        .is_some_and(|status| status.merkle_root == config.merkle_root)
+            }
+            CampaignScope::AuthorityCosign => accounts.campaign_cosigner.is_some(),
+        };
+        require!(eligible, ErrorCode::CampaignNotEligible);
+        let spent = balance_before_accounts.saturating_sub(accounts.minter.lamports());
+        let campaign_info = campaign.to_account_info();
+        let rent_floor = Rent::get()?.minimum_balance(campaign_info.data_len());
+        let available = campaign_info.lamports().saturating_sub(rent_floor);
+        let sponsored = spent.min(available);
+        
+        if sponsored > 0 {
+            **campaign_info.try_borrow_mut_lamports()? -= sponsored;
+            **accounts.minter.to_account_info().try_borrow_mut_lamports()? += sponsored;
+            campaign.total_sponsored = campaign
+                .total_sponsored
+                .checked_add(sponsored)
+                .ok_or(ErrorCode::Overflow)?;
+            msg!("Campaign {} sponsored {} lamports", campaign.id, sponsored);
+        }
+    }
+
+    msg!("NFT successfully minted!");
+    emit_edition_minted(
+        &config.master_mint,
+        accounts.minter.key(),
+        accounts.edition_mint.key(),
+        edition_number,
+        price,
+        is_discounted,
+    );
+    
+    Ok(price)
+}
+
+/// Accounts used to create a single edition NFT
+struct EditionAccounts<'info> {
+    /// Pays rent and acts as mint authority, update authority and verified creator
+    creator: AccountInfo<'info>,
+    /// Wallet that receives the edition token
+    owner: AccountInfo<'info>,
+    edition_mint: AccountInfo<'info>,
+    edition_token_account: AccountInfo<'info>,
+    edition_metadata: AccountInfo<'info>,
+    edition: AccountInfo<'info>,
+    token_metadata_program: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    rent: AccountInfo<'info>,
+}
+
+/// Create the mint, token account, metadata and master edition for one edition NFT
+fn create_edition_nft(
+    nft: &EditionAccounts,
+    config: &Config,
+    edition_number: u64,
+    is_mutable: bool,
+) -> Result<()> {
+    create_nft_token(nft)?;
+    create_nft_metadata(
+        nft,
+        DataV2 {
+            name: edition_name(config, edition_number)?,
+            symbol: config.symbol.clone(),
+            uri: config.uri.clone(),
+            seller_fee_basis_points: edition_seller_fee(config, edition_number),
+            creators: Some(edition_creators(config, &nft.creator.key())),
+            // Unverified collection reference
+            collection: Some(Collection {
+                verified: false,
+                key: config.master_mint,
+            }),
+            uses: None,
+        },
+        is_mutable,
+    )?;
+    create_nft_master_edition(nft, 0)
+}
+
+/// Create the mint and the owner's token account, and mint the single token. Mints are
+/// classic SPL mints sized by `Mint::LEN`; Token-2022 mints with extensions are not
+/// supported, since every path goes through the legacy token program and
+/// CreateMasterEditionV3.
+fn create_nft_token(nft: &EditionAccounts) -> Result<()> {
+    // Create mint account
+    let mint_rent = Rent::get()?.minimum_balance(spl_token::state::Mint::LEN);
+    invoke(
+        &system_instruction::create_account(
+            nft.creator.key,
+            nft.edition_mint.key,
+            mint_rent,
+            spl_token::state::Mint::LEN as u64,
+            &anchor_spl::token::ID,
+        ),
+        &[
+            nft.creator.clone(),
+            nft.edition_mint.clone(),
+            nft.system_program.clone(),
+        ],
+    )?;
+
+    // Initialize mint
+    invoke(
+        &spl_token::instruction::initialize_mint(
+            &anchor_spl::token::ID,
+            nft.edition_mint.key,
+            nft.creator.key,
+            Some(nft.creator.key),
+            0,
+        )?,
+        &[
+            nft.edition_mint.clone(),
+            nft.rent.clone(),
+        ],
+    )?;
+
+    // Create associated token account; idempotent so a pre-created account for an
+    // off-curve owner such as a smart wallet PDA is accepted
+    invoke(
+        &spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+            nft.creator.key,
+            nft.owner.key,
+            nft.edition_mint.key,
+            &anchor_spl::token::ID,
+        ),
+        &[
+            nft.creator.clone(),
+            nft.edition_token_account.clone(),
+            nft.owner.clone(),
+            nft.edition_mint.clone(),
+            nft.system_program.clone(),
+            nft.token_program.clone(),
+        ],
+    )?;
+
+    // Mint 1 token
+    invoke(
+        &spl_token::instruction::mint_to(
+            &anchor_spl::token::ID,
+            nft.edition_mint.key,
+            nft.edition_token_account.key,
+            nft.creator.key,
+            &[],
+            1,
+        )?,
+        &[
+            nft.edition_mint.clone(),
+            nft.edition_token_account.clone(),
+            nft.creator.clone(),
+        ],
+    )?;
+
+    Ok(())
+}
+
+/// Create the metadata account, with the creator as mint, update and payer authority
+fn create_nft_metadata(nft: &EditionAccounts, data: DataV2, is_mutable: bool) -> Result<()> {
+    let metadata_infos = vec![
+        nft.edition_metadata.clone(),
+        nft.edition_mint.clone(),
+        nft.creator.clone(),
+        nft.creator.clone(),
+        nft.creator.clone(),
+        nft.system_program.clone(),
+        nft.rent.clone(),
+    ];
+
+    invoke(
+        &mpl_token_metadata::instructions::CreateMetadataAccountV3 {
+            metadata: nft.edition_metadata.key(),
+            mint: nft.edition_mint.key(),
+            mint_authority: nft.creator.key(),
+            payer: nft.creator.key(),
+            update_authority: (nft.creator.key(), true),
+            system_program: nft.system_program.key(),
+            rent: Some(nft.rent.key()),
+        }
+        .instruction(mpl_token_metadata::instructions::CreateMetadataAccountV3InstructionArgs {
+            data,
+            is_mutable,
+            collection_details: None,
+        }),
+        metadata_infos.as_slice(),
+    )?;
+
+    Ok(())
+}
+
+/// Create the master edition; a max supply of 0 makes the token a 1/1
+fn create_nft_master_edition(nft: &EditionAccounts, max_supply: u64) -> Result<()> {
+    let master_edition_infos = vec![
+        nft.edition.clone(),
+        nft.edition_mint.clone(),
+        nft.creator.clone(),
+        nft.creator.clone(),
+        nft.edition_metadata.clone(),
+        nft.token_metadata_program.clone(),
+        nft.token_program.clone(),
+        nft.system_program.clone(),
+        nft.rent.clone(),
+    ];
+
+    invoke(
+        &mpl_token_metadata::instructions::CreateMasterEditionV3 {
+            edition: nft.edition.key(),
+            mint: nft.edition_mint.key(),
+            update_authority: nft.creator.key(),
+            mint_authority: nft.creator.key(),
+            payer: nft.creator.key(),
+            metadata: nft.edition_metadata.key(),
+            token_program: nft.token_program.key(),
+            system_program: nft.system_program.key(),
+            rent: Some(nft.rent.key()),
+        }
+        .instruction(mpl_token_metadata::instructions::CreateMasterEditionV3InstructionArgs {
+            max_supply: Some(max_supply),
+        }),
+        master_edition_infos.as_slice(),
+    )?;
+
+    // Token Metadata moves the mint authority to the edition PDA, so no one can mint a
+    // second token. Revoking it ourselves is not possible: before this CPI the authority
+    // must still sign, and afterwards the creator no longer holds it.
+    let mint = spl_token::state::Mint::unpack(&nft.edition_mint.try_borrow_data()?)?;
+    require!(
+        mint.mint_authority == COption::Some(nft.edition.key()) && mint.supply == 1,
+        ErrorCode::MintAuthorityNotRevoked
+    );
+
+    Ok(())
+}
+
+/// Mint an unpaid edition to `owner`, returning its edition number
+fn mint_unpaid_edition<'info>(accounts: &mut MintEdition<'info>, owner: AccountInfo<'info>) -> Result<u64> {
+    require_edition_pdas(&accounts.edition_mint, &accounts.edition_metadata, &accounts.edition)?;
+    let config = &mut accounts.config;
+    require_mint_open(config)?;
+    require_supply_available(config)?;
+    
+    let edition_number = next_edition_number(config, None)?;
+    
+    create_edition_nft(
+        &EditionAccounts {
+            creator: accounts.minter.to_account_info(),
+            owner,
+            edition_mint: accounts.edition_mint.to_account_info(),
+            edition_token_account: accounts.edition_token_account.to_account_info(),
+            edition_metadata: accounts.edition_metadata.to_account_info(),
+            edition: accounts.edition.to_account_info(),
+            token_metadata_program: accounts.token_metadata_program.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            system_program: accounts.system_program.to_account_info(),
+            rent: accounts.rent.to_account_info(),
+        },
+        config,
+        edition_number,
+        config.is_mutable,
+    )?;
+    record_genesis_mint(config, accounts.edition_mint.key());
+    
+    Ok(edition_number)
+}
+
+/// Mint an unpaid edition to the minter and freeze it with the config PDA as delegate and
+/// close authority, returning its edition number
+fn mint_frozen_edition(accounts: &mut MintEdition, config_bump: u8) -> Result<u64> {
+    let config_info = accounts.config.to_account_info();
+    let owner = accounts.minter.to_account_info();
+    let edition_number = mint_unpaid_edition(accounts, owner)?;
+    
+    anchor_spl::token::approve(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            anchor_spl::token::Approve {
+                to: accounts.edition_token_account.to_account_info(),
+                delegate: config_info.clone(),
+                authority: accounts.minter.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+    anchor_spl::token::set_authority(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            anchor_spl::token::SetAuthority {
+                current_authority: accounts.minter.to_account_info(),
+                account_or_mint: accounts.edition_token_account.to_account_info(),
+            },
+        ),
+        AuthorityType::CloseAccount,
+        Some(config_info.key()),
+    )?;
+    set_edition_frozen(
+        &config_info,
+        config_bump,
+        &accounts.edition_token_account,
+        &accounts.edition,
+        &accounts.edition_mint,
+        &accounts.token_program,
+        true,
+    )?;
+    
+    Ok(edition_number)
+}
+
+/// Freeze or thaw an edition through Token Metadata; the config PDA must be the
+/// token's delegate, since the master edition holds the mint's freeze authority
+fn set_edition_frozen<'info>(
+    config: &AccountInfo<'info>,
+    config_bump: u8,
+    token_account: &AccountInfo<'info>,
+    edition: &AccountInfo<'info>,
+    edition_mint: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    frozen: bool,
+) -> Result<()> {
+    let seeds = &[
+        b"config".as_ref(),
+        &[config_bump],
+    ];
+    let signer = &[&seeds[..]];
+    
+    let instruction = if frozen {
+        mpl_token_metadata::instructions::FreezeDelegatedAccount {
+            delegate: config.key(),
+            token_account: token_account.key(),
+            edition: edition.key(),
+            mint: edition_mint.key(),
+            token_program: token_program.key(),
+        }
+        .instruction()
+    } else {
+        mpl_token_metadata::instructions::ThawDelegatedAccount {
+            delegate: config.key(),
+            token_account: token_account.key(),
+            edition: edition.key(),
+            mint: edition_mint.key(),
+            token_program: token_program.key(),
+        }
+        .instruction()
+    };
+    
+    invoke_signed(
+        &instruction,
+        &[
+            config.clone(),
+            token_account.clone(),
+            edition.clone(),
+            edition_mint.clone(),
+            token_program.clone(),
+        ],
+        signer,
+    )?;
+    
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Payment vault PDA
+    #[account(
+        seeds = [b"payment_vault"],
+        bump
+    )]
+    pub payment_vault: AccountInfo<'info>,
+    
+    /// CHECK: Collection NFT metadata, validated against the master mint
+    pub master_metadata: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+    
+    /// Only the upgrade authority may initialize, so a deploy cannot be front-run
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::Unauthorized)]
+    pub program: Program<'info, crate::program::NftMinter>,
+    
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+}
+
+#[derive(Accounts)]
+pub struct MintEdition<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub minter: Signer<'info>,
+    
+    /// CHECK: Payment vault PDA
+    #[account(
+        mut,
+        seeds = [b"payment_vault"],
+        bump
+    )]
+    pub payment_vault: AccountInfo<'info>,
+    
+    /// CHECK: Master mint from config
+    pub master_mint: AccountInfo<'info>,
+    
+    /// CHECK: Master edition account
+    pub master_edition: AccountInfo<'info>,
+    
+    /// CHECK: Master metadata account
+    pub master_metadata: AccountInfo<'info>,
+    
+    /// CHECK: New edition mint account
+    #[account(mut, signer)]
+    pub edition_mint: Signer<'info>,
+    
+    /// CHECK: Edition token account for minter
+    #[account(mut)]
+    pub edition_token_account: AccountInfo<'info>,
+    
+    /// CHECK: Edition metadata account
+    #[account(mut)]
+    pub edition_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Edition account
+    #[account(mut)]
+    pub edition: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    
+    /// Optional gas subsidy campaign sponsoring this mint
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()],
+        bump = campaign.bump
+    )]
+    pub campaign: Option<Account<'info, Campaign>>,
+    
+    /// CHECK: Custodian from config, required when one is set
+    pub custodian: Option<AccountInfo<'info>>,
+    
+    /// CHECK: Pyth SOL/USD price update from config, required when the price is pegged to USD
+    pub price_feed: Option<AccountInfo<'info>>,
+    
+    #[account(
+        init_if_needed,
+        payer = minter,
+        space = 8 + MinterRecord::INIT_SPACE,
+        seeds = [b"minter", minter.key().as_ref()],
+        bump
+    )]
+    pub minter_record: Account<'info, MinterRecord>,
+    
+    /// Optional affiliate paid the referral cut of SOL payments directly
+    #[account(mut)]
+    pub referrer: Option<SystemAccount<'info>>,
+    
+    /// Referral code registered to the referrer, required with one and credited its mints
+    #[account(
+        mut,
+        seeds = [b"referral", referrer_code.code.as_bytes()],
+        bump = referrer_code.bump
+    )]
+    pub referrer_code: Option<Account<'info, ReferralCode>>,
+    
+    /// Collection NFT held by the minter, for the holder wallet limit
+    #[account(token::authority = minter)]
+    pub holder_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: Holder NFT metadata, validated against the holder token account's mint
+    pub holder_metadata: Option<AccountInfo<'info>>,
+    
+    /// Allowlist membership `register_allowlist` recorded, used by `mint_discounted` and
+    /// allowlist-scoped campaigns
+    #[account(
+        seeds = [b"allowlist", minter.key().as_ref()],
+        bump = allowlist_status.bump
+    )]
+    pub allowlist_status: Option<Account<'info, AllowlistStatus>>,
+    
+    /// Authority co-signing a mint sponsored by a co-sign campaign
+    #[account(address = config.authority @ ErrorCode::Unauthorized)]
+    pub campaign_cosigner: Option<Signer<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(edition_number: u64)]
+pub struct MintEditionAt<'info> {
+    pub mint: MintEdition<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = mint.minter,
+        space = 8 + EditionIndex::INIT_SPACE,
+        seeds = [b"edition_index", edition_number.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub edition_index: Account<'info, EditionIndex>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintWithReceipt<'info> {
+    pub mint: MintEdition<'info>,
+    
+    /// Authority co-signs to attest the metadata hash
+    #[account(address = mint.config.authority @ ErrorCode::Unauthorized)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = mint.minter,
+        space = 8 + MintReceipt::INIT_SPACE,
+        seeds = [b"receipt", mint.edition_mint.key().as_ref()],
+        bump
+    )]
+    pub receipt: Account<'info, MintReceipt>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePrintMaster<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = !config.finalized @ ErrorCode::ConfigFinalized
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    #[account(mut)]
+    pub print_master_mint: Signer<'info>,
+    
+    /// CHECK: Config PDA's token account for the print master, created here
+    #[account(mut)]
+    pub master_token_account: AccountInfo<'info>,
+    
+    /// CHECK: Print master metadata, created by Token Metadata
+    #[account(mut)]
+    pub print_master_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Print master edition, created by Token Metadata
+    #[account(mut)]
+    pub print_master_edition: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct PrintEdition<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub minter: Signer<'info>,
+    
+    /// CHECK: Payment vault PDA
+    #[account(
+        mut,
+        seeds = [b"payment_vault"],
+        bump
+    )]
+    pub payment_vault: AccountInfo<'info>,
+    
+    /// CHECK: Print master mint from config
+    #[account(address = config.print_master)]
+    pub print_master_mint: AccountInfo<'info>,
+    
+    /// CHECK: Config PDA's print master token account, validated by Token Metadata
+    pub master_token_account: AccountInfo<'info>,
+    
+    /// CHECK: Print master metadata, validated by Token Metadata
+    pub print_master_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Print master edition, whose supply Token Metadata increments
+    #[account(mut)]
+    pub print_master_edition: AccountInfo<'info>,
+    
+    /// CHECK: Edition marker PDA for this print number
+    #[account(mut)]
+    pub edition_marker: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub print_mint: Signer<'info>,
+    
+    /// CHECK: Minter's token account for the print
+    #[account(mut)]
+    pub print_token_account: AccountInfo<'info>,
+    
+    /// CHECK: Print metadata, created by Token Metadata
+    #[account(mut)]
+    pub print_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Print edition, created by Token Metadata
+    #[account(mut)]
+    pub print_edition: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    
+    #[account(
+        init_if_needed,
+        payer = minter,
+        space = 8 + MinterRecord::INIT_SPACE,
+        seeds = [b"minter", minter.key().as_ref()],
+        bump
+    )]
+    pub minter_record: Account<'info, MinterRecord>,
+    
+    /// CHECK: Pyth SOL/USD price update from config, required when the price is pegged to USD
+    pub price_feed: Option<AccountInfo<'info>>,
+    
+    /// Collection NFT held by the minter, for the holder wallet limit
+    #[account(token::authority = minter)]
+    pub holder_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: Holder NFT metadata, validated against the holder token account's mint
+    pub holder_metadata: Option<AccountInfo<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct MintByBurning<'info> {
+    pub mint: MintEdition<'info>,
+    
+    #[account(
+        mut,
+        constraint = mint.config.redeemable_mint == Some(voucher_mint.key()) @ ErrorCode::InvalidVoucher
+    )]
+    pub voucher_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = voucher_mint,
+        token::authority = mint.minter,
+        constraint = voucher_amount(voucher_mint.decimals)
+            .is_ok_and(|amount| voucher_token_account.amount >= amount) @ ErrorCode::InvalidVoucher
+    )]
+    pub voucher_token_account: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+pub struct MintWithToken<'info> {
+    pub mint: MintEdition<'info>,
+    
+    #[account(
+        address = mint.config.payment_mint,
+        constraint = mint.config.payment_mint != Pubkey::default() @ ErrorCode::TokenPaymentsDisabled
+    )]
+    pub payment_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = mint.minter
+    )]
+    pub minter_token_account: Account<'info, TokenAccount>,
+    
+    /// Program-owned vault for SPL payments, controlled by the config PDA
+    #[account(
+        init_if_needed,
+        payer = mint.minter,
+        seeds = [b"token_vault"],
+        bump,
+        token::mint = payment_mint,
+        token::authority = mint.config
+    )]
+    pub token_vault: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintRelayed<'info> {
+    /// The relayer signs as minter and pays the price and rent
+    pub mint: MintEdition<'info>,
+    
+    /// CHECK: User receiving the edition, authorized by an Ed25519 instruction
+    pub user: AccountInfo<'info>,
+    
+    /// CHECK: Instructions sysvar
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintGated<'info> {
+    pub mint: MintEdition<'info>,
+    
+    #[account(token::authority = mint.minter)]
+    pub gate_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Gate NFT metadata, validated against the gate token account's mint
+    pub gate_metadata: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintForStakers<'info> {
+    pub mint: MintEdition<'info>,
+    
+    /// CHECK: Stake account, validated against the configured staking program
+    pub stake_account: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(recipient_pda: Pubkey)]
+pub struct MintToPda<'info> {
+    pub mint: MintEdition<'info>,
+    
+    /// CHECK: Program-owned wallet receiving the edition
+    #[account(address = recipient_pda)]
+    pub recipient: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintPending<'info> {
+    pub mint: MintEdition<'info>,
+    
+    /// Authority co-signs to vouch for the off-chain payment
+    #[account(address = mint.config.authority @ ErrorCode::Unauthorized)]
+    pub authority: Signer<'info>,
+    
+    #[account(
+        init,
+        payer = mint.minter,
+        space = 8 + PendingMint::INIT_SPACE,
+        seeds = [b"pending", mint.edition_mint.key().as_ref()],
+        bump
+    )]
+    pub pending: Account<'info, PendingMint>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintVested<'info> {
+    /// The minter pays the account rent; the edition token account belongs to `vesting`
+    pub mint: MintEdition<'info>,
+    
+    #[account(address = mint.config.authority @ ErrorCode::Unauthorized)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Wallet the edition is released to, which need not sign
+    pub recipient: AccountInfo<'info>,
+    
+    #[account(
+        init,
+        payer = mint.minter,
+        space = 8 + VestingSchedule::INIT_SPACE,
+        seeds = [b"vesting", mint.edition_mint.key().as_ref()],
+        bump
+    )]
+    pub vesting: Account<'info, VestingSchedule>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminFreeze<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Edition mint being moderated
+    pub edition_mint: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        constraint = edition_token_account.mint == edition_mint.key() @ ErrorCode::InvalidMetadataAccount,
+        constraint = edition_token_account.delegate == COption::Some(config.key()) @ ErrorCode::EditionNotModeratable
+    )]
+    pub edition_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Master edition of the mint, validated by Token Metadata
+    pub edition: AccountInfo<'info>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + ModerationHold::INIT_SPACE,
+        seeds = [b"moderation", edition_mint.key().as_ref()],
+        bump
+    )]
+    pub moderation: Account<'info, ModerationHold>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AdminThaw<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Edition mint being moderated
+    pub edition_mint: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        constraint = edition_token_account.mint == edition_mint.key() @ ErrorCode::InvalidMetadataAccount,
+        constraint = edition_token_account.delegate == COption::Some(config.key()) @ ErrorCode::EditionNotModeratable
+    )]
+    pub edition_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Master edition of the mint, validated by Token Metadata
+    pub edition: AccountInfo<'info>,
+    
+    /// Absent for editions that were frozen on mint rather than by `admin_freeze`
+    #[account(
+        mut,
+        seeds = [b"moderation", edition_mint.key().as_ref()],
+        bump = moderation.bump,
+        close = authority
+    )]
+    pub moderation: Option<Account<'info, ModerationHold>>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BurnEdition<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    #[account(mut)]
+    pub edition_mint: Account<'info, Mint>,
+    
+    #[account(
+        mut,
+        token::mint = edition_mint,
+        token::authority = owner,
+        constraint = edition_token_account.amount == 1 @ ErrorCode::NotEditionOwner
+    )]
+    pub edition_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Edition metadata, validated against the edition mint
+    #[account(mut)]
+    pub edition_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Master edition of the mint, validated by Token Metadata
+    #[account(mut)]
+    pub edition: AccountInfo<'info>,
+    
+    /// CHECK: Collection metadata, required by BurnNft for verified collection members
+    #[account(mut)]
+    pub collection_metadata: Option<AccountInfo<'info>>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Unfreeze<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub owner: Signer<'info>,
+    
+    /// CHECK: Edition mint frozen on mint
+    pub edition_mint: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        token::authority = owner,
+        constraint = edition_token_account.mint == edition_mint.key() @ ErrorCode::InvalidMetadataAccount
+    )]
+    pub edition_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Master edition of the mint, validated by Token Metadata
+    pub edition: AccountInfo<'info>,
+    
+    /// CHECK: Pending editions are thawed through `settle_mint`
+    #[account(
+        seeds = [b"pending", edition_mint.key().as_ref()],
+        bump,
+        constraint = pending.data_is_empty() @ ErrorCode::MintLocked
+    )]
+    pub pending: AccountInfo<'info>,
+    
+    /// CHECK: Editions under an `admin_freeze` hold stay frozen until `admin_thaw`
+    #[account(
+        seeds = [b"moderation", edition_mint.key().as_ref()],
+        bump,
+        constraint = moderation.data_is_empty() @ ErrorCode::EditionModerated
+    )]
+    pub moderation: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"vesting", edition_mint.key().as_ref()],
+        bump = vesting.bump,
+        has_one = recipient,
+        close = recipient
+    )]
+    pub vesting: Account<'info, VestingSchedule>,
+    
+    #[account(address = vesting.mint @ ErrorCode::InvalidMetadataAccount)]
+    pub edition_mint: Account<'info, Mint>,
+    
+    /// Vesting PDA's token account holding the edition
+    #[account(
+        mut,
+        associated_token::mint = edition_mint,
+        associated_token::authority = vesting
+    )]
+    pub escrow_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        init_if_needed,
+        payer = recipient,
+        associated_token::mint = edition_mint,
+        associated_token::authority = recipient
+    )]
+    pub recipient_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        constraint = config.pending_authority == Some(pending_authority.key()) @ ErrorCode::NotPendingAuthority
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub pending_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    /// CHECK: Config in an earlier layout, which `Account<Config>` cannot deserialize;
+    /// the handler checks its discriminator and authority
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        owner = crate::ID
+    )]
+    pub config: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Collection NFT metadata, validated against the config's master mint
+    pub master_metadata: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CloseConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = config.paused @ ErrorCode::NotPaused,
+        close = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Payment vault PDA, must be empty
+    #[account(
+        seeds = [b"payment_vault"],
+        bump,
+        constraint = payment_vault.lamports() == 0 @ ErrorCode::VaultNotEmpty
+    )]
+    pub payment_vault: AccountInfo<'info>,
+    
+    /// CHECK: SPL token vault PDA, must be uncreated or empty
+    #[account(
+        seeds = [b"token_vault"],
+        bump
+    )]
+    pub token_vault: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CloseMinterRecord<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        constraint = config.finalized @ ErrorCode::DropNotFinalized
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub minter: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"minter", minter.key().as_ref()],
+        bump,
+        has_one = minter,
+        close = minter
+    )]
+    pub minter_record: Account<'info, MinterRecord>,
+}
+
+#[derive(Accounts)]
+pub struct ResolvePending<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"pending", edition_mint.key().as_ref()],
+        bump = pending.bump,
+        has_one = minter,
+        close = minter
+    )]
+    pub pending: Account<'info, PendingMint>,
+    
+    /// CHECK: Minter receiving the pending record's rent
+    #[account(mut)]
+    pub minter: AccountInfo<'info>,
+    
+    /// CHECK: Pending edition mint
+    #[account(mut)]
+    pub edition_mint: AccountInfo<'info>,
+    
+    #[account(
+        mut,
+        constraint = edition_token_account.mint == edition_mint.key() @ ErrorCode::InvalidMetadataAccount
+    )]
+    pub edition_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Master edition of the pending mint, validated by Token Metadata
+    pub edition: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyReceipt<'info> {
+    #[account(
+        seeds = [b"receipt", receipt.mint.as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, MintReceipt>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct MintWithReferral<'info> {
+    pub mint: MintEdition<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"referral", code.as_bytes()],
+        bump = referral_code.bump
+    )]
+    pub referral_code: Account<'info, ReferralCode>,
+}
+
+#[derive(Accounts)]
+pub struct AssertOwnsEdition<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub owner: Signer<'info>,
+    
+    pub token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Edition metadata, validated against the token account's mint
+    pub edition_metadata: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct GetMinterCount<'info> {
+    /// CHECK: May not exist yet; deserialized as a MinterRecord when it does
+    #[account(
+        seeds = [b"minter", wallet.as_ref()],
+        bump
+    )]
+    pub minter_record: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AssertVault<'info> {
+    /// CHECK: Derivation is what this instruction verifies
+    pub payment_vault: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct GetMinterRecords<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct GetConfig<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct ValidateBatch<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    /// CHECK: Wallet that would pay for the batch
+    pub minter: AccountInfo<'info>,
+    
+    /// Absent until the wallet's first mint
+    #[account(
+        seeds = [b"minter", minter.key().as_ref()],
+        bump
+    )]
+    pub minter_record: Option<Account<'info, MinterRecord>>,
+    
+    /// CHECK: Pyth SOL/USD price update from config, required when the price is pegged to USD
+    pub price_feed: Option<AccountInfo<'info>>,
+    
+    /// Collection NFT held by the minter, for the holder wallet limit
+    #[account(token::authority = minter)]
+    pub holder_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: Holder NFT metadata, validated against the holder token account's mint
+    pub holder_metadata: Option<AccountInfo<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct Eligibility<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    /// CHECK: Wallet whose eligibility is checked
+    pub minter: AccountInfo<'info>,
+    
+    /// Absent until the wallet's first mint
+    #[account(
+        seeds = [b"minter", minter.key().as_ref()],
+        bump
+    )]
+    pub minter_record: Option<Account<'info, MinterRecord>>,
+    
+    /// Collection NFT held by the minter, for the holder wallet limit
+    #[account(token::authority = minter)]
+    pub holder_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: Holder NFT metadata, validated against the holder token account's mint
+    pub holder_metadata: Option<AccountInfo<'info>>,
+}
+
+#[derive(Accounts)]
+#[instruction(epoch: u64)]
+pub struct SnapshotHolder<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Wallet whose editions are counted
+    pub holder: AccountInfo<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + HolderSnapshot::INIT_SPACE,
+        seeds = [b"snapshot", epoch.to_le_bytes().as_ref(), holder.key().as_ref()],
+        bump
+    )]
+    pub snapshot: Account<'info, HolderSnapshot>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyEditionIntegrity<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    /// CHECK: Edition mint
+    pub edition_mint: AccountInfo<'info>,
+    
+    /// CHECK: Edition metadata, validated against the edition mint
+    pub edition_metadata: AccountInfo<'info>,
+    
+    #[account(
+        seeds = [b"receipt", edition_mint.key().as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, MintReceipt>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimEditionAuthority<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    /// Current update authority of the edition, usually its original minter
+    pub update_authority: Signer<'info>,
+    
+    /// CHECK: Edition mint
+    pub edition_mint: AccountInfo<'info>,
+    
+    /// CHECK: Edition metadata, validated against the edition mint
+    #[account(mut)]
+    pub edition_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateEditionMetadata<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = !config.finalized @ ErrorCode::ConfigFinalized
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Edition mint
+    pub edition_mint: AccountInfo<'info>,
+    
+    /// CHECK: Edition metadata, validated against the edition mint
+    #[account(mut)]
+    pub edition_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetRevealHash<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = !config.finalized @ ErrorCode::ConfigFinalized
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"receipt", receipt.mint.as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, MintReceipt>,
+}
+
+#[derive(Accounts)]
+pub struct RevealWithSecret<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub holder: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"receipt", edition_mint.key().as_ref()],
+        bump = receipt.bump
+    )]
+    pub receipt: Account<'info, MintReceipt>,
+    
+    /// CHECK: Edition mint, tied to the receipt by its seeds
+    pub edition_mint: AccountInfo<'info>,
+    
+    #[account(
+        token::authority = holder,
+        token::mint = edition_mint,
+        constraint = holder_token_account.amount == 1 @ ErrorCode::InvalidRevealSecret
+    )]
+    pub holder_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Edition metadata, validated against the edition mint
+    #[account(mut)]
+    pub edition_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetCollectionRoyalty<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = !config.finalized @ ErrorCode::ConfigFinalized
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Collection metadata, validated against the master mint
+    #[account(mut)]
+    pub master_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRoyalties<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = !config.finalized @ ErrorCode::ConfigFinalized
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Collection metadata, validated against the master mint
+    pub master_metadata: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateRoyaltySplit<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = !config.finalized @ ErrorCode::ConfigFinalized
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Split program's wallet, omitted to restore the default creators
+    pub royalty_split_wallet: Option<AccountInfo<'info>>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMaxSupply<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = !config.finalized @ ErrorCode::ConfigFinalized
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Collection metadata, validated against the master mint
+    pub master_metadata: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct VerifyCollection<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        has_one = master_mint
+    )]
+    pub config: Account<'info, Config>,
+    
+    /// Signs as collection authority when it still holds the collection, and pays
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Edition mint whose metadata is verified
+    pub edition_mint: AccountInfo<'info>,
+    
+    /// CHECK: Edition metadata, validated against the edition mint
+    #[account(mut)]
+    pub edition_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Collection mint from config
+    pub master_mint: AccountInfo<'info>,
+    
+    /// CHECK: Collection metadata, validated against the master mint
+    #[account(mut)]
+    pub master_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Collection master edition, checked by Token Metadata
+    pub master_edition: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeCollection<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    /// Signs as update authority when it still holds the collection
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Collection metadata, validated against the master mint
+    #[account(mut)]
+    pub master_metadata: AccountInfo<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct MintAndList<'info> {
+    pub mint: MintEdition<'info>,
+    
+    /// CHECK: Listing escrow owner from config
+    #[account(address = mint.config.list_escrow)]
+    pub list_escrow: AccountInfo<'info>,
+    
+    /// CHECK: Escrow's token account for the edition, created idempotently
+    #[account(mut)]
+    pub escrow_token_account: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RegisterAllowlist<'info> {
+    #[account(
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(
+        init_if_needed,
+        payer = minter,
+        space = 8 + AllowlistStatus::INIT_SPACE,
+        seeds = [b"allowlist", minter.key().as_ref()],
+        bump
+    )]
+    pub allowlist_status: Account<'info, AllowlistStatus>,
+    
+    #[account(mut)]
+    pub minter: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct CreateReferralCode<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + ReferralCode::INIT_SPACE,
+        seeds = [b"referral", code.as_bytes()],
+        bump
+    )]
+    pub referral_code: Account<'info, ReferralCode>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct ClaimReferral<'info> {
+    #[account(
+        mut,
+        seeds = [b"referral", code.as_bytes()],
+        bump = referral_code.bump,
+        has_one = owner
+    )]
+    pub referral_code: Account<'info, ReferralCode>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct AirdropBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+pub struct MintBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
+    
+    #[account(mut)]
+    pub minter: Signer<'info>,
+    
+    /// CHECK: PDA vault receiving the batch payment
+    #[account(
+        mut,
+        seeds = [b"payment_vault"],
+        bump
+    )]
+    pub payment_vault: AccountInfo<'info>,
+    
+    #[account(
+        init_if_needed,
+        payer = minter,
+        space = 8 + MinterRecord::INIT_SPACE,
+        seeds = [b"minter", minter.key().as_ref()],
+        bump
+    )]
+    pub minter_record: Account<'info, MinterRecord>,
+    
+    /// CHECK: Pyth SOL/USD price update from config, required when the price is pegged to USD
+    pub price_feed: Option<AccountInfo<'info>>,
+    
+    /// CHECK: Token Metadata Program
+    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
+    pub token_metadata_program: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+    
+    /// Collection NFT held by the minter, for the holder wallet limit
+    #[account(token::authority = minter)]
+    pub holder_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: Holder NFT metadata, validated against the holder token account's mint
+    pub holder_metadata: Option<AccountInfo<'info>>,
+}
 
-    msg!("NFT successfully minted!");
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = !config.finalized @ ErrorCode::ConfigFinalized
+    )]
+    pub config: Account<'info, Config>,
     
-    Ok(())
+    #[account(mut)]
+    pub authority: Signer<'info>,
 }
 
 #[derive(Accounts)]
-pub struct Initialize<'info> {
+pub struct SetPaused<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawTokens<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + Config::INIT_SPACE,
         seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
+    pub config: Account<'info, Config>,
+    
+    pub authority: Signer<'info>,
+    
+    #[account(
+        mut,
+        seeds = [b"token_vault"],
         bump
     )]
+    pub token_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = config.payment_mint
+    )]
+    pub destination: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority
+    )]
     pub config: Account<'info, Config>,
     
     #[account(mut)]
@@ -293,6 +5766,7 @@ pub struct Initialize<'info> {
     
     /// CHECK: Payment vault PDA
     #[account(
+        mut,
         seeds = [b"payment_vault"],
         bump
     )]
@@ -302,17 +5776,15 @@ pub struct Initialize<'info> {
 }
 
 #[derive(Accounts)]
-pub struct MintEdition<'info> {
+pub struct AutoSweep<'info> {
     #[account(
         mut,
         seeds = [b"config"],
-        bump
+        bump,
+        has_one = creator_wallet
     )]
     pub config: Account<'info, Config>,
     
-    #[account(mut)]
-    pub minter: Signer<'info>,
-    
     /// CHECK: Payment vault PDA
     #[account(
         mut,
@@ -321,57 +5793,139 @@ pub struct MintEdition<'info> {
     )]
     pub payment_vault: AccountInfo<'info>,
     
-    /// CHECK: Master mint from config
-    pub master_mint: AccountInfo<'info>,
+    /// CHECK: Creator wallet from config
+    #[account(mut)]
+    pub creator_wallet: AccountInfo<'info>,
     
-    /// CHECK: Master edition account
-    pub master_edition: AccountInfo<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateCompressedTree<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump,
+        has_one = authority,
+        constraint = !config.finalized @ ErrorCode::ConfigFinalized
+    )]
+    pub config: Account<'info, Config>,
     
-    /// CHECK: Master metadata account
-    pub master_metadata: AccountInfo<'info>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
     
-    /// CHECK: New edition mint account
-    #[account(mut, signer)]
-    pub edition_mint: Signer<'info>,
+    /// CHECK: Bubblegum tree config PDA, initialized by Bubblegum
+    #[account(mut)]
+    pub tree_config: AccountInfo<'info>,
     
-    /// CHECK: Edition token account for minter
+    /// CHECK: Pre-allocated Merkle tree account, initialized by account compression
     #[account(mut)]
-    pub edition_token_account: AccountInfo<'info>,
+    pub merkle_tree: AccountInfo<'info>,
+    
+    /// CHECK: SPL Noop program
+    #[account(address = mpl_bubblegum::programs::SPL_NOOP_ID)]
+    pub log_wrapper: AccountInfo<'info>,
+    
+    /// CHECK: SPL Account Compression program
+    #[account(address = mpl_bubblegum::programs::SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: AccountInfo<'info>,
+    
+    /// CHECK: Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: AccountInfo<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MintCompressed<'info> {
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump
+    )]
+    pub config: Account<'info, Config>,
     
-    /// CHECK: Edition metadata account
     #[account(mut)]
-    pub edition_metadata: AccountInfo<'info>,
+    pub minter: Signer<'info>,
     
-    /// CHECK: Edition account
+    /// CHECK: Payment vault PDA
+    #[account(
+        mut,
+        seeds = [b"payment_vault"],
+        bump
+    )]
+    pub payment_vault: AccountInfo<'info>,
+    
+    /// CHECK: Bubblegum tree config PDA, validated by Bubblegum
     #[account(mut)]
-    pub edition: AccountInfo<'info>,
+    pub tree_config: AccountInfo<'info>,
     
-    /// CHECK: Token Metadata Program
-    #[account(address = TOKEN_METADATA_PROGRAM_ID)]
-    pub token_metadata_program: AccountInfo<'info>,
+    /// CHECK: Merkle tree from config
+    #[account(mut, address = config.merkle_tree)]
+    pub merkle_tree: AccountInfo<'info>,
+    
+    /// CHECK: SPL Noop program
+    #[account(address = mpl_bubblegum::programs::SPL_NOOP_ID)]
+    pub log_wrapper: AccountInfo<'info>,
+    
+    /// CHECK: SPL Account Compression program
+    #[account(address = mpl_bubblegum::programs::SPL_ACCOUNT_COMPRESSION_ID)]
+    pub compression_program: AccountInfo<'info>,
+    
+    /// CHECK: Bubblegum program
+    #[account(address = mpl_bubblegum::ID)]
+    pub bubblegum_program: AccountInfo<'info>,
     
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
+    
+    #[account(
+        init_if_needed,
+        payer = minter,
+        space = 8 + MinterRecord::INIT_SPACE,
+        seeds = [b"minter", minter.key().as_ref()],
+        bump
+    )]
+    pub minter_record: Account<'info, MinterRecord>,
+    
+    /// CHECK: Pyth SOL/USD price update from config, required when the price is pegged to USD
+    pub price_feed: Option<AccountInfo<'info>>,
+    
+    /// Collection NFT held by the minter, for the holder wallet limit
+    #[account(token::authority = minter)]
+    pub holder_token_account: Option<Account<'info, TokenAccount>>,
+    
+    /// CHECK: Holder NFT metadata, validated against the holder token account's mint
+    pub holder_metadata: Option<AccountInfo<'info>>,
 }
 
 #[derive(Accounts)]
-pub struct UpdateConfig<'info> {
+#[instruction(campaign_id: u64)]
+pub struct CreateCampaign<'info> {
     #[account(
-        mut,
         seeds = [b"config"],
         bump,
         has_one = authority
     )]
     pub config: Account<'info, Config>,
     
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + Campaign::INIT_SPACE,
+        seeds = [b"campaign", campaign_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub campaign: Account<'info, Campaign>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct Withdraw<'info> {
+pub struct UpdateCampaign<'info> {
     #[account(
         seeds = [b"config"],
         bump,
@@ -379,16 +5933,27 @@ pub struct Withdraw<'info> {
     )]
     pub config: Account<'info, Config>,
     
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()],
+        bump = campaign.bump
+    )]
+    pub campaign: Account<'info, Campaign>,
     
-    /// CHECK: Payment vault PDA
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct FundCampaign<'info> {
     #[account(
         mut,
-        seeds = [b"payment_vault"],
-        bump
+        seeds = [b"campaign", campaign.id.to_le_bytes().as_ref()],
+        bump = campaign.bump
     )]
-    pub payment_vault: AccountInfo<'info>,
+    pub campaign: Account<'info, Campaign>,
+    
+    #[account(mut)]
+    pub funder: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
@@ -402,6 +5967,341 @@ pub struct Config {
     pub discounted_price: u64,
     pub total_minted: u64,
     pub payment_vault: Pubkey,
+    // The fields above are the first release layout `migrate_config` grows from. Fields
+    // below were added since and are not append-only: `max_supply`, `payment_mint` and
+    // `token_price` moved to the end, so only first release accounts can be migrated
+    pub is_mutable: bool,
+    pub allow_immutable: bool,
+    pub min_minter_balance: u64,
+    pub merkle_tree: Pubkey,
+    pub creator_wallet: Pubkey,
+    pub auto_sweep_threshold: u64,
+    pub price_change_cooldown: i64,
+    pub last_price_change_ts: i64,
+    pub referral_basis_points: u16,
+    pub max_referral_mints: u64,
+    pub referral_cap_rejects: bool,
+    pub auto_list_enabled: bool,
+    pub list_escrow: Pubkey,
+    pub finalized: bool,
+    /// Full SPL delegate of every new edition token, revocable by the holder
+    pub custodian: Option<Pubkey>,
+    pub end_slot: u64,
+    pub allow_admin_adjust: bool,
+    #[max_len(MAX_RARITY_TIERS)]
+    pub rarity_tiers: Vec<RarityTier>,
+    pub paused: bool,
+    pub print_master: Pubkey,
+    pub prints_minted: u64,
+    pub max_per_wallet: u64,
+    pub gate_collection: Option<Pubkey>,
+    pub gate_update_authority: Pubkey,
+    pub min_effective_price: u64,
+    #[max_len(MAX_BASE_NAME_LEN)]
+    pub base_name: String,
+    #[max_len(MAX_SYMBOL_LENGTH)]
+    pub symbol: String,
+    #[max_len(MAX_URI_LENGTH)]
+    pub uri: String,
+    pub royalty_basis_points: u16,
+    pub royalty_recipient: Pubkey,
+    pub pending_authority: Option<Pubkey>,
+    pub mint_start_ts: i64,
+    pub mint_end_ts: i64,
+    /// Allowlist root for discounted mints; all zeroes leaves the discount open
+    pub merkle_root: [u8; 32],
+    /// Regular price pegged to USD through the SOL/USD feed; 0 keeps the lamport price
+    pub usd_price_cents: u64,
+    pub price_feed: Pubkey,
+    /// Oldest feed publish time accepted, in seconds
+    pub max_price_age: u64,
+    /// Withdrawals are capped to a linearly vesting share of collected funds (0 duration disables)
+    pub vesting_start: i64,
+    pub vesting_duration: i64,
+    pub total_withdrawn: u64,
+    /// Mint of the first edition NFT, written once
+    pub genesis_mint: Option<Pubkey>,
+    pub price_grace_period: i64,
+    /// Regular lamport price, flat or on the curve, before the last raise, charged instead
+    /// until `price_locked_until`
+    pub previous_mint_price: u64,
+    pub price_locked_until: i64,
+    /// Program owning the stake accounts accepted by `mint_for_stakers`
+    pub staking_program: Option<Pubkey>,
+    pub min_stake: u64,
+    /// Public free mints available through `mint_free`, and how many were claimed
+    pub free_supply: u64,
+    pub free_minted: u64,
+    /// Per-wallet limit for verified collection holders, 0 falls back to `max_per_wallet`
+    pub holder_max_per_wallet: u64,
+    /// New editions are frozen in the minter's wallet until `unlock_ts`
+    pub freeze_on_mint: bool,
+    pub unlock_ts: i64,
+    /// Mint counts announced with a `Milestone` event, see `update_milestones`
+    pub milestone_interval: u64,
+    #[max_len(MAX_MILESTONES)]
+    pub milestones: Vec<u64>,
+    /// URI prefix for editions revealed with their claim code
+    #[max_len(MAX_REVEAL_BASE_URI_LEN)]
+    pub reveal_base_uri: String,
+    /// Voucher mint redeemed one whole token per edition by `mint_by_burning`
+    pub redeemable_mint: Option<Pubkey>,
+    /// Regular price follows base_price + price_increment * total_minted when enabled
+    pub curve_enabled: bool,
+    pub base_price: u64,
+    pub price_increment: u64,
+    /// Editions burned through `burn_edition`; `total_minted` is never decremented
+    pub total_burned: u64,
+    /// Full-share creator of new editions when set, so royalties flow into a split program
+    pub royalty_split_wallet: Option<Pubkey>,
+    /// Account discriminator a stake account must start with for `mint_for_stakers`
+    pub stake_discriminator: [u8; 8],
+    /// Pyth feed id the price update must carry, e.g. SOL/USD
+    pub price_feed_id: [u8; 32],
+    /// Widest accepted oracle confidence interval, in basis points of the price
+    pub max_confidence_bps: u16,
+    /// USD price before the last raise, charged instead until `price_locked_until`
+    pub previous_usd_price_cents: u64,
+    /// Edition numbers at the top of `max_supply` that only `mint_edition_at` may claim
+    pub reserved_editions: u64,
+    /// Reserved editions claimed so far, also counted in `total_minted`
+    pub reserved_minted: u64,
+    pub max_supply: u64,
+    pub payment_mint: Pubkey,
+    pub token_price: u64,
+    /// Mint transactions one wallet may send per UTC day, 0 is unlimited
+    pub max_mints_per_day: u64,
+    /// Set once `finalize_collection` locked the sold-out collection
+    pub collection_finalized: bool,
+    /// Per-method kill switches for lamport and SPL token payments
+    pub sol_mint_enabled: bool,
+    pub token_mint_enabled: bool,
+    /// Lamports and payment-mint tokens `mint_split_payment` charges together; 0 for
+    /// either leaves split payments off
+    pub split_sol_price: u64,
+    pub split_token_price: u64,
+    /// How the discounted price is set: `discounted_price`, or `discount_ratio_bps` of the
+    /// current regular price so it follows the bonding curve
+    pub discount_mode: DiscountMode,
+    pub discount_ratio_bps: u16,
+    /// Regular-price batches of at least a tier's size get its discount, see `update_bulk_tiers`
+    #[max_len(MAX_BULK_TIERS)]
+    pub bulk_tiers: Vec<BulkTier>,
+    /// Ceiling on every seller fee the authority can set, see `update_max_royalty`
+    pub max_royalty_bps: u16,
+    /// Highest edition number handed out sequentially, which `reconcile_supply` can't undercut
+    pub highest_sequential_edition: u64,
+}
+
+/// A wallet's mint history returned by `get_minter_receipt` and `get_minter_records`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MinterSummary {
+    pub minter: Pubkey,
+    pub count: u64,
+    pub discounted_count: u64,
+    pub last_mint_ts: i64,
+    pub lifetime_count: u64,
+}
+
+/// Stable subset of `Config` returned by `get_config`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ConfigView {
+    pub authority: Pubkey,
+    pub mint_price: u64,
+    pub discounted_price: u64,
+    pub token_price: u64,
+    pub total_minted: u64,
+    pub max_supply: u64,
+    pub paused: bool,
+}
+
+/// Discount off the whole batch for regular-price batches of `min_quantity` or more
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct BulkTier {
+    pub min_quantity: u64,
+    pub discount_bps: u16,
+}
+
+/// Source of the discounted lamport price
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum DiscountMode {
+    /// The flat `discounted_price`
+    Fixed,
+    /// `discount_ratio_bps` of the current regular price
+    RatioOfRegular,
+}
+
+/// Royalty for editions numbered up to `max_edition` not covered by an earlier tier
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, InitSpace)]
+pub struct RarityTier {
+    pub max_edition: u64,
+    pub seller_fee_basis_points: u16,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct EditionIndex {
+    pub edition_number: u64,
+    pub mint: Pubkey,
+    pub claimed: bool,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MintReceipt {
+    pub mint: Pubkey,
+    pub minter: Pubkey,
+    pub edition_number: u64,
+    pub metadata_hash: [u8; 32],
+    /// sha256(master_mint || edition_number), reproducible regardless of metadata changes
+    pub serial_hash: [u8; 32],
+    pub bump: u8,
+    /// sha256 of the claim code that reveals the edition, zeroed once revealed
+    pub reveal_hash: [u8; 32],
+    /// `edition_content_hash` of the name, symbol and URI the edition was minted or revealed with
+    pub content_hash: [u8; 32],
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ReferralCode {
+    pub owner: Pubkey,
+    #[max_len(32)]
+    pub code: String,
+    pub unclaimed: u64,
+    pub total_earned: u64,
+    pub mints: u64,
+    pub bump: u8,
+}
+
+/// A wallet's allowlist proof verified against `merkle_root`
+#[account]
+#[derive(InitSpace)]
+pub struct AllowlistStatus {
+    pub minter: Pubkey,
+    pub merkle_root: [u8; 32],
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct MinterRecord {
+    pub minter: Pubkey,
+    /// Editions minted, counted against the wallet limit
+    pub count: u64,
+    /// Editions of `count` minted at the discounted price
+    pub discounted_count: u64,
+    pub last_mint_ts: i64,
+    /// Mint transactions, a batch counting once
+    pub lifetime_count: u64,
+    /// UTC day (unix time / 86400) of `daily_count`
+    pub day: i64,
+    /// Mint transactions sent on `day`
+    pub daily_count: u64,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct PendingMint {
+    pub mint: Pubkey,
+    pub minter: Pubkey,
+    pub edition_number: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct ModerationHold {
+    pub mint: Pubkey,
+    pub frozen_at: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct VestingSchedule {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub vesting_end: i64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct HolderSnapshot {
+    pub holder: Pubkey,
+    pub epoch: u64,
+    pub edition_count: u64,
+    pub bump: u8,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Campaign {
+    pub id: u64,
+    pub total_sponsored: u64,
+    pub active: bool,
+    pub scope: CampaignScope,
+    pub bump: u8,
+}
+
+/// Which minters a campaign sponsors
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum CampaignScope {
+    /// Wallets registered through `register_allowlist` for the current root
+    Allowlist,
+    /// Any wallet, when the authority co-signs the mint
+    AuthorityCosign,
+}
+
+#[event]
+pub struct EditionMinted {
+    pub minter: Pubkey,
+    pub mint: Pubkey,
+    pub edition_number: u64,
+    pub price: u64,
+    pub discounted: bool,
+    pub serial_hash: [u8; 32],
+}
+
+#[event]
+pub struct Milestone {
+    pub total_minted: u64,
+    pub max_supply: u64,
+}
+
+#[event]
+pub struct SoldOut {
+    pub total_minted: u64,
+}
+
+#[event]
+pub struct CollectionComplete {
+    pub master_mint: Pubkey,
+    pub total_minted: u64,
+    pub total_burned: u64,
+    pub max_supply: u64,
+    /// Size of a sized collection, 0 when unsized
+    pub collection_size: u64,
+}
+
+#[event]
+pub struct FundsWithdrawn {
+    pub authority: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PricingUpdated {
+    pub regular_price: u64,
+    pub discounted_price: u64,
+}
+
+#[event]
+pub struct SupplyReconciled {
+    pub previous: u64,
+    pub total_minted: u64,
+    pub delta: i128,
 }
 
 #[error_code]
@@ -412,4 +6312,291 @@ pub enum ErrorCode {
     InvalidMasterMint,
     #[msg("Insufficient payment")]
     InsufficientPayment,
+    #[msg("Immutable mints are not allowed")]
+    ImmutableMintsForbidden,
+    #[msg("Minter balance is below the required minimum")]
+    MinterBalanceTooLow,
+    #[msg("Compressed minting is not configured")]
+    CompressedMintingDisabled,
+    #[msg("Config account is not the canonical PDA")]
+    InvalidConfig,
+    #[msg("Edition number has already been minted")]
+    EditionTaken,
+    #[msg("Edition numbers start at 1")]
+    InvalidEditionNumber,
+    #[msg("Airdrop accounts must come in full sets per recipient")]
+    InvalidAirdropAccounts,
+    #[msg("Too many recipients in one airdrop")]
+    AirdropBatchTooLarge,
+    #[msg("Auto-sweep threshold must keep the vault rent exempt")]
+    InvalidSweepThreshold,
+    #[msg("Auto-sweep is disabled")]
+    AutoSweepDisabled,
+    #[msg("Vault balance is below the sweep threshold")]
+    VaultBelowSweepThreshold,
+    #[msg("Metadata hash does not match the mint receipt")]
+    MetadataHashMismatch,
+    #[msg("Price was changed too recently")]
+    PriceChangeTooSoon,
+    #[msg("Cooldown cannot be negative")]
+    InvalidCooldown,
+    #[msg("Basis points cannot exceed 10000")]
+    InvalidBasisPoints,
+    #[msg("Referral code must be 1-32 bytes")]
+    InvalidReferralCode,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Mint-and-list is disabled")]
+    AutoListDisabled,
+    #[msg("Referral code has reached its mint cap")]
+    ReferralCapReached,
+    #[msg("Drop is finalized and its configuration can no longer change")]
+    ConfigFinalized,
+    #[msg("Signer does not own an edition of this collection")]
+    NotEditionOwner,
+    #[msg("Metadata account does not match the edition mint")]
+    InvalidMetadataAccount,
+    #[msg("Edition metadata does not match the values it was minted with")]
+    MetadataDrift,
+    #[msg("Custodian account does not match the config")]
+    InvalidCustodian,
+    #[msg("Snapshot accounts must be holder-owned editions of this collection")]
+    InvalidSnapshotAccounts,
+    #[msg("Minting has ended")]
+    MintEnded,
+    #[msg("Admin adjustments are disabled")]
+    AdminAdjustDisabled,
+    #[msg("Rarity tiers must ascend by edition and fit the tier table")]
+    InvalidRarityTiers,
+    #[msg("Missing or invalid Ed25519 relay authorization")]
+    InvalidRelayAuthorization,
+    #[msg("Payment vault did not receive the mint price")]
+    PaymentNotReceived,
+    #[msg("Max supply reached")]
+    MaxSupplyReached,
+    #[msg("Max supply cannot be lowered below the minted count")]
+    InvalidMaxSupply,
+    #[msg("Vesting end must be in the future")]
+    InvalidVestingSchedule,
+    #[msg("Edition is still vesting")]
+    VestingNotEnded,
+    #[msg("Minting is paused")]
+    MintingPaused,
+    #[msg("Print supply must be greater than zero")]
+    InvalidPrintSupply,
+    #[msg("No print master has been created")]
+    PrintsDisabled,
+    #[msg("Wallet has reached its mint limit")]
+    WalletLimitReached,
+    #[msg("Token payments are not configured")]
+    TokenPaymentsDisabled,
+    #[msg("Gated minting is not configured")]
+    GateDisabled,
+    #[msg("Gate NFT is not a verified member of the gate collection")]
+    InvalidGateNft,
+    #[msg("Effective price is below the configured floor")]
+    PriceTooLow,
+    #[msg("Name, symbol or URI is empty or exceeds Metaplex limits")]
+    InvalidMetadataConfig,
+    #[msg("Drop has not been finalized")]
+    DropNotFinalized,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("Edition creators must include the collection's primary creator")]
+    MissingCollectionCreator,
+    #[msg("Minting has not started yet")]
+    MintNotStarted,
+    #[msg("Mint window must start before it ends")]
+    InvalidMintWindow,
+    #[msg("Wallet is not on the allowlist")]
+    NotAllowlisted,
+    #[msg("Price feed is stale or invalid")]
+    StaleOracle,
+    #[msg("Edition mint authority was not handed to the master edition")]
+    MintAuthorityNotRevoked,
+    #[msg("Vault holds nothing above its rent-exempt minimum")]
+    NothingToWithdraw,
+    #[msg("Withdrawal exceeds the vested amount")]
+    ExceedsVested,
+    #[msg("Vesting duration cannot be negative")]
+    InvalidWithdrawalVesting,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Vault balance is lower than the requested amount")]
+    InsufficientVaultBalance,
+    #[msg("Minting must be paused first")]
+    NotPaused,
+    #[msg("Payment vault still holds funds")]
+    VaultNotEmpty,
+    #[msg("Minter does not hold the required stake")]
+    NotStaking,
+    #[msg("Free mint supply is exhausted")]
+    FreeSupplyExhausted,
+    #[msg("A referrer account cannot be combined with a referral code")]
+    ReferrerWithReferralCode,
+    #[msg("Account is not the payment vault for this bump")]
+    InvalidVault,
+    #[msg("Holder NFT is missing or not a verified collection member")]
+    NotAHolder,
+    #[msg("Editions stay frozen until the unlock time")]
+    MintLocked,
+    #[msg("Milestones must be ascending, non-zero and at most eight")]
+    InvalidMilestones,
+    #[msg("Mint batch accounts must be a fresh signer mint, token account, metadata and edition per edition")]
+    InvalidMintBatchAccounts,
+    #[msg("Mint batch quantity must be between 1 and 5")]
+    MintBatchTooLarge,
+    #[msg("Reveal secret does not match this edition")]
+    InvalidRevealSecret,
+    #[msg("Voucher is missing or not the configured redeemable mint")]
+    InvalidVoucher,
+    #[msg("Max supply exceeds the collection size")]
+    SupplyExceedsCollection,
+    #[msg("Minting is still open")]
+    MintStillOpen,
+    #[msg("Royalty split wallet must be a funded, non-program account other than the recipient")]
+    InvalidRoyaltySplit,
+    #[msg("Referrer must own a registered referral code and be neither the minter nor the recipient")]
+    InvalidReferrer,
+    #[msg("Edition is frozen for moderation")]
+    EditionModerated,
+    #[msg("Reserved editions must fit within max supply and cannot move once one is claimed")]
+    InvalidReservedEditions,
+    #[msg("Config already has the current layout")]
+    ConfigAlreadyMigrated,
+    #[msg("Config is not in a layout this program can migrate")]
+    UnknownConfigLayout,
+    #[msg("Expected each minter wallet followed by its minter record")]
+    InvalidMinterRecordAccounts,
+    #[msg("Too many minter records for one read")]
+    TooManyMinterRecords,
+    #[msg("Wallet reached its daily mint limit")]
+    DailyLimitReached,
+    #[msg("Every edition of the capped supply must be minted first")]
+    NotSoldOut,
+    #[msg("Collection is already finalized")]
+    CollectionFinalized,
+    #[msg("This payment method is disabled")]
+    PaymentMethodDisabled,
+    #[msg("Split payments are not configured")]
+    SplitPaymentDisabled,
+    #[msg("Bulk tiers must start at two editions and ascend by size and discount")]
+    InvalidBulkTiers,
+    #[msg("Royalty exceeds the configured ceiling")]
+    RoyaltyExceedsCeiling,
+    #[msg("Creator shares must add up to 100")]
+    InvalidCreatorShares,
+    #[msg("Reconciled count is outside the minted and reserved ranges")]
+    InvalidReconciledCount,
+    #[msg("Campaign is not active")]
+    CampaignInactive,
+    #[msg("Minter is not eligible for this campaign")]
+    CampaignNotEligible,
+    #[msg("Edition does not delegate to the config, so it cannot be moderated")]
+    EditionNotModeratable,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_config_is_accepted() {
+        let (config, _) = Pubkey::find_program_address(&[b"config"], &crate::ID);
+        assert!(require_canonical_config(&config).is_ok());
+    }
+
+    #[test]
+    fn spoofed_config_is_rejected() {
+        let spoofed = Pubkey::new_unique();
+        assert!(require_canonical_config(&spoofed).is_err());
+    }
+
+    #[test]
+    fn daily_limit_resets_on_the_next_day() {
+        let mut record = MinterRecord {
+            minter: Pubkey::new_unique(),
+            count: 0,
+            discounted_count: 0,
+            last_mint_ts: 0,
+            lifetime_count: 0,
+            day: 0,
+            daily_count: 0,
+        };
+        let morning = 19_000 * 86_400 + 3_600;
+        record_daily_mint(&mut record, 2, morning).unwrap();
+        record_daily_mint(&mut record, 2, morning + 60).unwrap();
+        assert!(record_daily_mint(&mut record, 2, morning + 120).is_err());
+        assert_eq!(record.daily_count, 2);
+        
+        record_daily_mint(&mut record, 2, morning + 86_400).unwrap();
+        assert_eq!(record.day, 19_001);
+        assert_eq!(record.daily_count, 1);
+        
+        // 0 leaves the throttle off
+        for _ in 0..5 {
+            record_daily_mint(&mut record, 0, morning + 86_400).unwrap();
+        }
+    }
+
+    #[test]
+    fn migrated_first_release_config_can_mint() {
+        let authority = Pubkey::new_unique();
+        let master_mint = Pubkey::new_unique();
+        let payment_vault = Pubkey::new_unique();
+        let mut data = Vec::with_capacity(8 + Config::INIT_SPACE);
+        data.extend_from_slice(&<Config as anchor_lang::Discriminator>::DISCRIMINATOR);
+        data.extend_from_slice(authority.as_ref());
+        data.extend_from_slice(master_mint.as_ref());
+        data.extend_from_slice(&200_000_000u64.to_le_bytes());
+        data.extend_from_slice(&100_000_000u64.to_le_bytes());
+        data.extend_from_slice(&7u64.to_le_bytes());
+        data.extend_from_slice(payment_vault.as_ref());
+        assert_eq!(data.len(), FIRST_RELEASE_CONFIG_LEN);
+        data.resize(8 + Config::INIT_SPACE, 0);
+        
+        let royalty_recipient = Pubkey::new_unique();
+        migrate_first_release(&mut data, royalty_recipient, 0).unwrap();
+        let config = Config::try_deserialize(&mut &data[..]).unwrap();
+        
+        assert_eq!(config.authority, authority);
+        assert_eq!(config.master_mint, master_mint);
+        assert_eq!(config.mint_price, 200_000_000);
+        assert_eq!(config.total_minted, 7);
+        assert_eq!(config.payment_vault, payment_vault);
+        
+        // The checks and metadata of the next mint match what the first release produced
+        assert!(config.is_mutable || config.allow_immutable);
+        assert!(!config.paused && config.mint_start_ts == 0 && config.mint_end_ts == 0);
+        require_valid_metadata_config(&config.base_name, &config.symbol, &config.uri).unwrap();
+        assert_eq!(edition_name(&config, 8).unwrap(), "AMMo Founder #8");
+        assert_eq!(config.symbol, FIRST_RELEASE_SYMBOL);
+        assert_eq!(config.uri, FIRST_RELEASE_URI);
+        assert_eq!(edition_seller_fee(&config, 8), 500);
+        let minter = Pubkey::new_unique();
+        let creators = edition_creators(&config, &minter);
+        assert_eq!(creators[0].address, royalty_recipient);
+        assert_eq!(creators[0].share, 100);
+        assert_eq!(regular_price_at(&config, config.total_minted, 0).unwrap(), 200_000_000);
+        assert!(config.sol_mint_enabled && config.token_mint_enabled);
+        assert_eq!(config.max_royalty_bps, 10_000);
+    }
+
+    #[test]
+    fn reserved_editions_above_the_cap_overflow() {
+        let mut data = vec![0; 8 + Config::INIT_SPACE];
+        data[..8].copy_from_slice(&<Config as anchor_lang::Discriminator>::DISCRIMINATOR);
+        let mut config = Config::try_deserialize(&mut &data[..]).unwrap();
+        config.max_supply = 2;
+        config.reserved_editions = 3;
+        
+        assert_eq!(sequential_cap(&config).unwrap_err(), error!(ErrorCode::Overflow));
+        assert_eq!(reserved_range(&config).unwrap_err(), error!(ErrorCode::Overflow));
+        assert_eq!(next_edition_number(&mut config, None).unwrap_err(), error!(ErrorCode::Overflow));
+        assert_eq!(config.total_minted, 0);
+        
+        config.reserved_editions = 1;
+        assert_eq!(reserved_range(&config).unwrap(), 2..=2);
+        assert_eq!(next_edition_number(&mut config, None).unwrap(), 1);
+    }
 }
\ No newline at end of file
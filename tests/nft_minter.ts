@@ -1,16 +1,4279 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
+import {
+  ComputeBudgetProgram,
+  Ed25519Program,
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+  SYSVAR_RENT_PUBKEY,
+  Transaction,
+  TransactionInstruction,
+} from "@solana/web3.js";
+import { expect } from "chai";
+import { createHash } from "crypto";
+import { readFileSync } from "fs";
+import { keccak_256 } from "@noble/hashes/sha3";
 import { NftMinter } from "../target/types/nft_minter";
 
+const TOKEN_METADATA_PROGRAM_ID = new PublicKey(
+  "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bgsBTsFF"
+);
+const BUBBLEGUM_PROGRAM_ID = new PublicKey(
+  "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY"
+);
+const ACCOUNT_COMPRESSION_PROGRAM_ID = new PublicKey(
+  "cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK"
+);
+const NOOP_PROGRAM_ID = new PublicKey(
+  "noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV"
+);
+const TOKEN_PROGRAM_ID = anchor.utils.token.TOKEN_PROGRAM_ID;
+const ASSOCIATED_TOKEN_PROGRAM_ID = anchor.utils.token.ASSOCIATED_PROGRAM_ID;
+
 describe("nft_minter", () => {
   // Configure the client to use the local cluster.
-  anchor.setProvider(anchor.AnchorProvider.env());
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
 
   const program = anchor.workspace.nftMinter as Program<NftMinter>;
+  const authority = provider.wallet.publicKey;
+  const masterMintKeypair = Keypair.generate();
+  const masterMint = masterMintKeypair.publicKey;
+  // Mock USDC accepted by mint_with_token
+  const paymentMintKeypair = Keypair.generate();
+  const paymentMint = paymentMintKeypair.publicKey;
+  const tokenPrice = new anchor.BN(5_000_000);
+  const editionName = "AMMo Founder";
+  const editionSymbol = "FAMMo";
+  const editionUri =
+    "https://plum-imperial-swordfish-193.mypinata.cloud/ipfs/bafkreiddegzxdo2h3sliwjfpp22f46mfwb7frb3aibdqtln74uiiv3wkmy";
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("config")],
+    program.programId
+  );
+  const [paymentVault] = PublicKey.findProgramAddressSync(
+    [Buffer.from("payment_vault")],
+    program.programId
+  );
+  const [tokenVault] = PublicKey.findProgramAddressSync(
+    [Buffer.from("token_vault")],
+    program.programId
+  );
+
+  const metadataPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+  const editionPda = (mint: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+  const fundedWallet = async (sol = 2) => {
+    const wallet = Keypair.generate();
+    const sig = await provider.connection.requestAirdrop(
+      wallet.publicKey,
+      sol * LAMPORTS_PER_SOL
+    );
+    await provider.connection.confirmTransaction(sig);
+    return wallet;
+  };
+
+  const minterRecordPda = (minter: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("minter"), minter.toBuffer()],
+      program.programId
+    )[0];
+
+  const mintAccounts = (minter: PublicKey, editionMint: PublicKey) => ({
+    config: configPda,
+    minter,
+    paymentVault,
+    masterMint,
+    masterEdition: editionPda(masterMint),
+    masterMetadata: metadataPda(masterMint),
+    editionMint,
+    editionTokenAccount: anchor.utils.token.associatedAddress({
+      mint: editionMint,
+      owner: minter,
+    }),
+    editionMetadata: metadataPda(editionMint),
+    edition: editionPda(editionMint),
+    tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+    tokenProgram: TOKEN_PROGRAM_ID,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+    rent: SYSVAR_RENT_PUBKEY,
+    campaign: null,
+    custodian: null,
+    priceFeed: null,
+    minterRecord: minterRecordPda(minter),
+    referrer: null,
+    referrerCode: null,
+    holderTokenAccount: null,
+    holderMetadata: null,
+    allowlistStatus: null,
+    campaignCosigner: null,
+  });
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    let error: any;
+    try {
+      await promise;
+    } catch (err) {
+      error = err;
+    }
+    expect(error, `expected ${code}`).to.exist;
+    expect(error.error?.errorCode?.code).to.equal(code);
+  };
+
+  const sleep = (ms: number) => new Promise((resolve) => setTimeout(resolve, ms));
+
+  const computeBudget = [
+    ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }),
+  ];
+
+  // Marks an edition as a verified member of the collection
+  const verifyEdition = (editionMint: PublicKey) =>
+    program.methods
+      .verifyCollection()
+      .accountsPartial({
+        config: configPda,
+        authority,
+        editionMint,
+        editionMetadata: metadataPda(editionMint),
+        masterMint,
+        masterMetadata: metadataPda(masterMint),
+        masterEdition: editionPda(masterMint),
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .rpc();
+
+  // Decodes the leading fields of a Token Metadata account.
+  const fetchMetadata = async (mint: PublicKey) => {
+    const info = await provider.connection.getAccountInfo(metadataPda(mint));
+    const data = info!.data;
+    let offset = 1 + 32 + 32;
+    const readString = () => {
+      const len = data.readUInt32LE(offset);
+      const value = data
+        .subarray(offset + 4, offset + 4 + len)
+        .toString("utf8")
+        .replace(/\0/g, "");
+      offset += 4 + len;
+      return value;
+    };
+    const name = readString();
+    const symbol = readString();
+    const uri = readString();
+    const sellerFeeBasisPoints = data.readUInt16LE(offset);
+    offset += 2;
+    const creators: { address: PublicKey; verified: boolean; share: number }[] =
+      [];
+    if (data[offset++] === 1) {
+      const count = data.readUInt32LE(offset);
+      offset += 4;
+      for (let i = 0; i < count; i++) {
+        creators.push({
+          address: new PublicKey(data.subarray(offset, offset + 32)),
+          verified: data[offset + 32] === 1,
+          share: data[offset + 33],
+        });
+        offset += 34;
+      }
+    }
+    const primarySaleHappened = data[offset++] === 1;
+    const isMutable = data[offset++] === 1;
+    // Skip edition_nonce and token_standard options
+    offset += data[offset] === 1 ? 2 : 1;
+    offset += data[offset] === 1 ? 2 : 1;
+    let collection: { verified: boolean; key: PublicKey } | null = null;
+    if (data[offset++] === 1) {
+      collection = {
+        verified: data[offset] === 1,
+        key: new PublicKey(data.subarray(offset + 1, offset + 33)),
+      };
+    }
+    return {
+      updateAuthority: new PublicKey(data.subarray(1, 33)),
+      name,
+      symbol,
+      uri,
+      sellerFeeBasisPoints,
+      creators,
+      primarySaleHappened,
+      isMutable,
+      collection,
+    };
+  };
+
+  const borshString = (value: string) => {
+    const bytes = Buffer.from(value, "utf8");
+    const len = Buffer.alloc(4);
+    len.writeUInt32LE(bytes.length);
+    return Buffer.concat([len, bytes]);
+  };
+
+  const u64 = (value: number) => {
+    const buf = Buffer.alloc(8);
+    buf.writeBigUInt64LE(BigInt(value));
+    return buf;
+  };
+
+  const key = (pubkey: PublicKey, isSigner = false, isWritable = false) => ({
+    pubkey,
+    isSigner,
+    isWritable,
+  });
+
+  // Raw SPL Token instructions paid for and authorized by the provider wallet
+  const createMintIxs = async (mint: PublicKey, decimals: number) => [
+    SystemProgram.createAccount({
+      fromPubkey: authority,
+      newAccountPubkey: mint,
+      lamports: await provider.connection.getMinimumBalanceForRentExemption(82),
+      space: 82,
+      programId: TOKEN_PROGRAM_ID,
+    }),
+    // InitializeMint with the wallet as mint and freeze authority
+    new TransactionInstruction({
+      programId: TOKEN_PROGRAM_ID,
+      keys: [key(mint, false, true), key(SYSVAR_RENT_PUBKEY)],
+      data: Buffer.concat([
+        Buffer.from([0, decimals]),
+        authority.toBuffer(),
+        Buffer.from([1]),
+        authority.toBuffer(),
+      ]),
+    }),
+  ];
+
+  const createAtaIx = (mint: PublicKey, owner: PublicKey) =>
+    new TransactionInstruction({
+      programId: ASSOCIATED_TOKEN_PROGRAM_ID,
+      keys: [
+        key(authority, true, true),
+        key(anchor.utils.token.associatedAddress({ mint, owner }), false, true),
+        key(owner),
+        key(mint),
+        key(SystemProgram.programId),
+        key(TOKEN_PROGRAM_ID),
+      ],
+      data: Buffer.alloc(0),
+    });
+
+  const mintToIx = (mint: PublicKey, destination: PublicKey, amount: number) =>
+    new TransactionInstruction({
+      programId: TOKEN_PROGRAM_ID,
+      keys: [key(mint, false, true), key(destination, false, true), key(authority, true)],
+      data: Buffer.concat([Buffer.from([7]), u64(amount)]),
+    });
+
+  // Creates a one-of-one collection NFT owned by the provider wallet, built from
+  // raw SPL Token and Token Metadata instructions.
+  const createCollectionNft = async (mint: Keypair) => {
+    const owner = authority;
+    const tokenAccount = anchor.utils.token.associatedAddress({
+      mint: mint.publicKey,
+      owner,
+    });
+    const metadata = metadataPda(mint.publicKey);
+    const edition = editionPda(mint.publicKey);
+
+    const tx = new Transaction().add(
+      ...(await createMintIxs(mint.publicKey, 0)),
+      createAtaIx(mint.publicKey, owner),
+      mintToIx(mint.publicKey, tokenAccount, 1),
+      // CreateMetadataAccountV3 with the owner as sole verified creator
+      new TransactionInstruction({
+        programId: TOKEN_METADATA_PROGRAM_ID,
+        keys: [
+          key(metadata, false, true),
+          key(mint.publicKey),
+          key(owner, true),
+          key(owner, true, true),
+          key(owner, true),
+          key(SystemProgram.programId),
+          key(SYSVAR_RENT_PUBKEY),
+        ],
+        data: Buffer.concat([
+          Buffer.from([33]),
+          borshString("AMMo Founder Collection"),
+          borshString("FAMMo"),
+          borshString("https://example.com/collection.json"),
+          Buffer.from([0xf4, 0x01]),
+          Buffer.from([1, 1, 0, 0, 0]),
+          owner.toBuffer(),
+          Buffer.from([1, 100]),
+          Buffer.from([0, 0, 1, 0]),
+        ]),
+      }),
+      // CreateMasterEditionV3 with max_supply Some(0)
+      new TransactionInstruction({
+        programId: TOKEN_METADATA_PROGRAM_ID,
+        keys: [
+          key(edition, false, true),
+          key(mint.publicKey, false, true),
+          key(owner, true),
+          key(owner, true),
+          key(owner, true, true),
+          key(metadata, false, true),
+          key(TOKEN_PROGRAM_ID),
+          key(SystemProgram.programId),
+          key(SYSVAR_RENT_PUBKEY),
+        ],
+        data: Buffer.concat([Buffer.from([17, 1]), u64(0)]),
+      })
+    );
+    await provider.sendAndConfirm(tx, [mint]);
+  };
+
+  before(async () => {
+    await createCollectionNft(masterMintKeypair);
+    await provider.sendAndConfirm(
+      new Transaction().add(...(await createMintIxs(paymentMint, 6))),
+      [paymentMintKeypair]
+    );
+  });
+
+  const [programData] = PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
+
+  // Signed by the provider wallet, the program's upgrade authority, unless another signer is given
+  const initializeConfig = ({
+    signer,
+    collection = masterMint,
+    maxSupply = 0,
+    royaltyRecipient = authority,
+  }: {
+    signer?: Keypair;
+    collection?: PublicKey;
+    maxSupply?: number;
+    royaltyRecipient?: PublicKey;
+  } = {}) =>
+    program.methods
+      .initialize(
+        collection,
+        new anchor.BN(maxSupply),
+        paymentMint,
+        tokenPrice,
+        editionName,
+        editionSymbol,
+        editionUri,
+        500,
+        royaltyRecipient,
+        new anchor.BN(0),
+        new anchor.BN(0)
+      )
+      .accountsPartial({
+        config: configPda,
+        authority: signer?.publicKey ?? authority,
+        paymentVault,
+        masterMetadata: metadataPda(collection),
+        systemProgram: SystemProgram.programId,
+        program: program.programId,
+        programData,
+      })
+      .signers(signer ? [signer] : [])
+      .rpc();
 
   it("Is initialized!", async () => {
-    // Add your test here.
-    const tx = await program.methods.initialize().rpc();
+    const tx = await initializeConfig();
     console.log("Your transaction signature", tx);
+
+    const config = await program.account.config.fetch(configPda);
+    expect(config.authority.toBase58()).to.equal(authority.toBase58());
+    expect(config.masterMint.toBase58()).to.equal(masterMint.toBase58());
+  });
+
+  it("Records the first edition's mint as the genesis mint", async () => {
+    expect((await program.account.config.fetch(configPda)).genesisMint).to.equal(null);
+
+    const minter = await fundedWallet();
+    const mints = [Keypair.generate(), Keypair.generate()];
+    for (const editionMint of mints) {
+      await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+      const { genesisMint } = await program.account.config.fetch(configPda);
+      expect(genesisMint?.toBase58()).to.equal(mints[0].publicKey.toBase58());
+    }
+  });
+
+  it("Mints immutable and mutable editions per mint", async () => {
+    const minter = await fundedWallet();
+
+    const immutableMint = Keypair.generate();
+    await program.methods
+      .mintWithMutability(false)
+      .accountsPartial(mintAccounts(minter.publicKey, immutableMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, immutableMint])
+      .rpc();
+
+    const mutableMint = Keypair.generate();
+    await program.methods
+      .mintWithMutability(true)
+      .accountsPartial(mintAccounts(minter.publicKey, mutableMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, mutableMint])
+      .rpc();
+
+    expect((await fetchMetadata(immutableMint.publicKey)).isMutable).to.be
+      .false;
+    expect((await fetchMetadata(mutableMint.publicKey)).isMutable).to.be.true;
+  });
+
+  it("Rejects immutable mints when the policy forbids them", async () => {
+    await program.methods
+      .updateMutability(null, false)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await expectError(
+      program.methods
+        .mintWithMutability(false)
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc(),
+      "ImmutableMintsForbidden"
+    );
+
+    await program.methods
+      .updateMutability(null, true)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Gates mints on a minimum minter balance", async () => {
+    await program.methods
+      .updateMinMinterBalance(new anchor.BN(LAMPORTS_PER_SOL))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    // 1 SOL cannot cover the 0.2 SOL price plus the 1 SOL floor
+    const poorMinter = await fundedWallet(1);
+    const poorMint = Keypair.generate();
+    await expectError(
+      program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(poorMinter.publicKey, poorMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([poorMinter, poorMint])
+        .rpc(),
+      "MinterBalanceTooLow"
+    );
+
+    const richMinter = await fundedWallet(3);
+    const richMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(richMinter.publicKey, richMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([richMinter, richMint])
+      .rpc();
+
+    await program.methods
+      .updateMinMinterBalance(new anchor.BN(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Sponsors mint rent from a campaign until it is exhausted", async () => {
+    const campaignId = new anchor.BN(1);
+    const [campaign] = PublicKey.findProgramAddressSync(
+      [Buffer.from("campaign"), campaignId.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    );
+    await program.methods
+      .createCampaign(campaignId, { authorityCosign: {} })
+      .accountsPartial({ config: configPda, campaign, authority })
+      .rpc();
+    const funding = new anchor.BN(0.05 * LAMPORTS_PER_SOL);
+    await program.methods
+      .fundCampaign(funding)
+      .accountsPartial({ campaign, funder: authority })
+      .rpc();
+
+    const price = (await program.account.config.fetch(configPda)).mintPrice;
+    const minter = await fundedWallet(5);
+    const mintUnder = (cosigner: PublicKey | null) => {
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintEdition()
+        .accountsPartial({
+          ...mintAccounts(minter.publicKey, editionMint.publicKey),
+          campaign,
+          campaignCosigner: cosigner,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+    const mintSponsored = async () => {
+      const before = await provider.connection.getBalance(minter.publicKey);
+      await mintUnder(authority);
+      return before - (await provider.connection.getBalance(minter.publicKey));
+    };
+
+    // Strangers can't draw on a co-sign campaign, nor anyone on an inactive one
+    await expectError(mintUnder(null), "CampaignNotEligible");
+    const setActive = (active: boolean) =>
+      program.methods
+        .setCampaignActive(active)
+        .accountsPartial({ config: configPda, campaign, authority })
+        .rpc();
+    await setActive(false);
+    await expectError(mintUnder(authority), "CampaignInactive");
+    await setActive(true);
+
+    // A funded campaign covers everything except the price
+    expect(await mintSponsored()).to.equal(price.toNumber());
+
+    const rentFloor =
+      await provider.connection.getMinimumBalanceForRentExemption(
+        (await provider.connection.getAccountInfo(campaign))!.data.length
+      );
+    for (let i = 0; i < 10; i++) {
+      if ((await provider.connection.getBalance(campaign)) <= rentFloor) break;
+      await mintSponsored();
+    }
+
+    // Once exhausted the minter pays rent again
+    expect(await mintSponsored()).to.be.greaterThan(price.toNumber());
+    const state = await program.account.campaign.fetch(campaign);
+    expect(state.totalSponsored.toString()).to.equal(funding.toString());
+  });
+
+  it("Mints a compressed edition into the configured tree", async () => {
+    // Depth 3 / buffer 8 tree without canopy: 56 byte header + 1248 byte tree
+    const merkleTree = Keypair.generate();
+    const treeSize = 1304;
+    const [treeConfig] = PublicKey.findProgramAddressSync(
+      [merkleTree.publicKey.toBuffer()],
+      BUBBLEGUM_PROGRAM_ID
+    );
+    const allocateTree = SystemProgram.createAccount({
+      fromPubkey: authority,
+      newAccountPubkey: merkleTree.publicKey,
+      lamports:
+        await provider.connection.getMinimumBalanceForRentExemption(treeSize),
+      space: treeSize,
+      programId: ACCOUNT_COMPRESSION_PROGRAM_ID,
+    });
+    const compressionAccounts = {
+      config: configPda,
+      treeConfig,
+      merkleTree: merkleTree.publicKey,
+      logWrapper: NOOP_PROGRAM_ID,
+      compressionProgram: ACCOUNT_COMPRESSION_PROGRAM_ID,
+      bubblegumProgram: BUBBLEGUM_PROGRAM_ID,
+      systemProgram: SystemProgram.programId,
+    };
+    await program.methods
+      .createCompressedTree(3, 8)
+      .accountsPartial({ ...compressionAccounts, authority })
+      .preInstructions([allocateTree])
+      .signers([merkleTree])
+      .rpc();
+
+    // TreeConfig: discriminator, creator, delegate, capacity, then num_minted
+    const numMinted = async () =>
+      (await provider.connection.getAccountInfo(treeConfig))!.data.readBigUInt64LE(
+        8 + 32 + 32 + 8
+      );
+    const before = await numMinted();
+
+    const minter = await fundedWallet();
+    await program.methods
+      .mintCompressed()
+      .accountsPartial({
+        ...compressionAccounts,
+        minter: minter.publicKey,
+        paymentVault,
+        priceFeed: null,
+        minterRecord: minterRecordPda(minter.publicKey),
+        holderTokenAccount: null,
+        holderMetadata: null,
+      })
+      .preInstructions(computeBudget)
+      .signers([minter])
+      .rpc();
+
+    expect(await numMinted()).to.equal(before + BigInt(1));
+    // Compressed mints count against the wallet limit like any other
+    const record = await program.account.minterRecord.fetch(
+      minterRecordPda(minter.publicKey)
+    );
+    expect(record.count.toNumber()).to.equal(1);
+  });
+
+  it("Airdrops an edition to each of three recipients", async () => {
+    const recipients = [0, 1, 2].map(() => Keypair.generate().publicKey);
+    const editionMints = recipients.map(() => Keypair.generate());
+    const remainingAccounts = recipients.flatMap((recipient, i) => {
+      const editionMint = editionMints[i].publicKey;
+      return [
+        { pubkey: recipient, isSigner: false, isWritable: false },
+        { pubkey: editionMint, isSigner: true, isWritable: true },
+        {
+          pubkey: anchor.utils.token.associatedAddress({
+            mint: editionMint,
+            owner: recipient,
+          }),
+          isSigner: false,
+          isWritable: true,
+        },
+        { pubkey: metadataPda(editionMint), isSigner: false, isWritable: true },
+        { pubkey: editionPda(editionMint), isSigner: false, isWritable: true },
+      ];
+    });
+
+    await program.methods
+      .airdropBatch()
+      .accountsPartial({
+        config: configPda,
+        authority,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .remainingAccounts(remainingAccounts)
+      .preInstructions([
+        ComputeBudgetProgram.setComputeUnitLimit({ units: 1_200_000 }),
+      ])
+      .signers(editionMints)
+      .rpc();
+
+    for (const [i, recipient] of recipients.entries()) {
+      const balance = await provider.connection.getTokenAccountBalance(
+        anchor.utils.token.associatedAddress({
+          mint: editionMints[i].publicKey,
+          owner: recipient,
+        })
+      );
+      expect(balance.value.amount).to.equal("1");
+    }
+  });
+
+  it("Sweeps vault funds above the threshold to the creator wallet", async () => {
+    const creatorWallet = Keypair.generate().publicKey;
+    const threshold = new anchor.BN(
+      (await provider.connection.getBalance(paymentVault)) + LAMPORTS_PER_SOL
+    );
+    await program.methods
+      .updateAutoSweep(creatorWallet, threshold)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    // Push the vault 0.3 SOL past the threshold
+    const excess = 0.3 * LAMPORTS_PER_SOL;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: authority,
+          toPubkey: paymentVault,
+          lamports: LAMPORTS_PER_SOL + excess,
+        })
+      )
+    );
+
+    // The sweep takes no signer accounts, so any fee payer can crank it
+    const sweep = () =>
+      program.methods
+        .autoSweep()
+        .accountsPartial({
+          config: configPda,
+          paymentVault,
+          creatorWallet,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+
+    // Nothing has vested before the withdrawal vesting starts
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .updateWithdrawalVesting(new anchor.BN(now + 3600), new anchor.BN(3600))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await expectError(sweep(), "ExceedsVested");
+    await program.methods
+      .updateWithdrawalVesting(new anchor.BN(0), new anchor.BN(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    await sweep();
+
+    expect(await provider.connection.getBalance(creatorWallet)).to.equal(excess);
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(
+      threshold.toNumber()
+    );
+
+    await program.methods
+      .updateAutoSweep(authority, new anchor.BN(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Records and verifies an authority-signed metadata hash", async () => {
+    const metadataHash = Array.from(
+      Buffer.from(anchor.utils.sha256.hash("edition metadata json"), "hex")
+    );
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const [receipt] = PublicKey.findProgramAddressSync(
+      [Buffer.from("receipt"), editionMint.publicKey.toBuffer()],
+      program.programId
+    );
+
+    // The provider wallet is the authority and co-signs the transaction
+    await program.methods
+      .mintWithMetadataHash(metadataHash)
+      .accountsPartial({
+        mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+        authority,
+        receipt,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    await program.methods
+      .verifyMetadataHash(metadataHash)
+      .accountsPartial({ receipt })
+      .rpc();
+
+    const tampered = [...metadataHash];
+    tampered[0] ^= 0xff;
+    await expectError(
+      program.methods.verifyMetadataHash(tampered).accountsPartial({ receipt }).rpc(),
+      "MetadataHashMismatch"
+    );
+  });
+
+  it("Enforces a cooldown between price changes", async () => {
+    const { mintPrice } = await program.account.config.fetch(configPda);
+    await program.methods
+      .updatePriceCooldown(new anchor.BN(2))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    await program.methods
+      .updatePricing(mintPrice, null, null)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await expectError(
+      program.methods
+        .updatePricing(mintPrice, null, null)
+        .accountsPartial({ config: configPda, authority })
+        .rpc(),
+      "PriceChangeTooSoon"
+    );
+
+    await sleep(4000);
+    await program.methods
+      .updatePricing(mintPrice, null, null)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    await program.methods
+      .updatePriceCooldown(new anchor.BN(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Accrues referral rewards on a code and lets the affiliate claim them", async () => {
+    const code = "ALICE";
+    const [referralCode] = PublicKey.findProgramAddressSync(
+      [Buffer.from("referral"), Buffer.from(code)],
+      program.programId
+    );
+    const affiliate = await fundedWallet(1);
+    await program.methods
+      .createReferralCode(code)
+      .accountsPartial({
+        referralCode,
+        owner: affiliate.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([affiliate])
+      .rpc();
+    await program.methods
+      .updateReferralRate(1_000)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    const { mintPrice } = await program.account.config.fetch(configPda);
+    const mintReferred = async (minter: Keypair) => {
+      const editionMint = Keypair.generate();
+      await program.methods
+        .mintWithReferral(code)
+        .accountsPartial({
+          mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+          referralCode,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    for (let i = 0; i < 2; i++) {
+      await mintReferred(await fundedWallet());
+    }
+
+    // The cut is split off the payment, the same way a direct referrer is paid
+    const expected = mintPrice.muln(2).divn(10);
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(
+      vaultBefore + mintPrice.muln(2).sub(expected).toNumber()
+    );
+    await expectError(mintReferred(affiliate), "InvalidReferrer");
+    const accrued = await program.account.referralCode.fetch(referralCode);
+    expect(accrued.unclaimed.toString()).to.equal(expected.toString());
+    expect(accrued.mints.toNumber()).to.equal(2);
+
+    const before = await provider.connection.getBalance(affiliate.publicKey);
+    await program.methods
+      .claimReferral(code)
+      .accountsPartial({ referralCode, owner: affiliate.publicKey })
+      .signers([affiliate])
+      .rpc();
+    expect(await provider.connection.getBalance(affiliate.publicKey)).to.equal(
+      before + expected.toNumber()
+    );
+    expect(
+      (await program.account.referralCode.fetch(referralCode)).unclaimed.toNumber()
+    ).to.equal(0);
+
+    await program.methods
+      .updateReferralRate(0)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Sizes edition mints from the token program's mint layout", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    const info = await provider.connection.getAccountInfo(editionMint.publicKey);
+    expect(info!.owner.toBase58()).to.equal(TOKEN_PROGRAM_ID.toBase58());
+    expect(info!.data.length).to.equal(82);
+  });
+
+  it("Mints and lists an edition into the configured escrow", async () => {
+    const listEscrow = Keypair.generate().publicKey;
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const listAccounts = {
+      mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+      listEscrow,
+      escrowTokenAccount: anchor.utils.token.associatedAddress({
+        mint: editionMint.publicKey,
+        owner: listEscrow,
+      }),
+    };
+
+    await program.methods
+      .updateAutoList(false, listEscrow)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await expectError(
+      program.methods
+        .mintAndList()
+        .accountsPartial(listAccounts)
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc(),
+      "AutoListDisabled"
+    );
+
+    await program.methods
+      .updateAutoList(true, null)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    // A frozen token could not be moved into the escrow
+    await program.methods
+      .updateMintLock(true, new anchor.BN(Math.floor(Date.now() / 1000) + 3600))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await expectError(
+      program.methods
+        .mintAndList()
+        .accountsPartial(listAccounts)
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc(),
+      "MintLocked"
+    );
+    await program.methods
+      .updateMintLock(false, new anchor.BN(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    await program.methods
+      .mintAndList()
+      .accountsPartial(listAccounts)
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    const escrowBalance = await provider.connection.getTokenAccountBalance(
+      listAccounts.escrowTokenAccount
+    );
+    expect(escrowBalance.value.amount).to.equal("1");
+    const minterBalance = await provider.connection.getTokenAccountBalance(
+      listAccounts.mint.editionTokenAccount
+    );
+    expect(minterBalance.value.amount).to.equal("0");
+
+    await program.methods
+      .updateAutoList(false, null)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Stops referral rewards once a code hits its mint cap", async () => {
+    const code = "BOB";
+    const [referralCode] = PublicKey.findProgramAddressSync(
+      [Buffer.from("referral"), Buffer.from(code)],
+      program.programId
+    );
+    const affiliate = await fundedWallet(1);
+    await program.methods
+      .createReferralCode(code)
+      .accountsPartial({
+        referralCode,
+        owner: affiliate.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([affiliate])
+      .rpc();
+    await program.methods
+      .updateReferralRate(1_000)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await program.methods
+      .updateReferralCap(new anchor.BN(1), false)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    const mintReferred = async () => {
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintWithReferral(code)
+        .accountsPartial({
+          mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+          referralCode,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+    const mintDirect = async () => {
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintEdition()
+        .accountsPartial({
+          ...mintAccounts(minter.publicKey, editionMint.publicKey),
+          referrer: affiliate.publicKey,
+          referrerCode: referralCode,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+
+    await mintReferred();
+    const earned = (await program.account.referralCode.fetch(referralCode))
+      .unclaimed;
+    expect(earned.toNumber()).to.be.greaterThan(0);
+
+    // Over the cap the mint succeeds but the code earns nothing more
+    await mintReferred();
+    const capped = await program.account.referralCode.fetch(referralCode);
+    expect(capped.unclaimed.toString()).to.equal(earned.toString());
+    expect(capped.mints.toNumber()).to.equal(1);
+
+    // Supplying the affiliate directly is held to the same cap
+    const affiliateBefore = await provider.connection.getBalance(affiliate.publicKey);
+    await mintDirect();
+    expect(await provider.connection.getBalance(affiliate.publicKey)).to.equal(affiliateBefore);
+    const direct = await program.account.referralCode.fetch(referralCode);
+    expect(direct.mints.toNumber()).to.equal(1);
+    expect(direct.totalEarned.toString()).to.equal(capped.totalEarned.toString());
+
+    await program.methods
+      .updateReferralCap(new anchor.BN(1), true)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await expectError(mintReferred(), "ReferralCapReached");
+    await expectError(mintDirect(), "ReferralCapReached");
+
+    await program.methods
+      .updateReferralCap(new anchor.BN(0), false)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await program.methods
+      .updateReferralRate(0)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Asserts edition ownership for holders only", async () => {
+    const owner = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const accounts = mintAccounts(owner.publicKey, editionMint.publicKey);
+    await program.methods
+      .mintEdition()
+      .accountsPartial(accounts)
+      .preInstructions(computeBudget)
+      .signers([owner, editionMint])
+      .rpc();
+
+    const ownershipAccounts = (signer: PublicKey) => ({
+      config: configPda,
+      owner: signer,
+      tokenAccount: accounts.editionTokenAccount,
+      editionMetadata: accounts.editionMetadata,
+    });
+    // Only verified members of the collection count
+    await expectError(
+      program.methods
+        .assertOwnsEdition()
+        .accountsPartial(ownershipAccounts(owner.publicKey))
+        .signers([owner])
+        .rpc(),
+      "NotEditionOwner"
+    );
+
+    await verifyEdition(editionMint.publicKey);
+    await program.methods
+      .assertOwnsEdition()
+      .accountsPartial(ownershipAccounts(owner.publicKey))
+      .signers([owner])
+      .rpc();
+
+    const stranger = await fundedWallet(1);
+    await expectError(
+      program.methods
+        .assertOwnsEdition()
+        .accountsPartial(ownershipAccounts(stranger.publicKey))
+        .signers([stranger])
+        .rpc(),
+      "NotEditionOwner"
+    );
+  });
+
+  it("Lets a minter hand update authority to the config PDA", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const accounts = mintAccounts(minter.publicKey, editionMint.publicKey);
+    await program.methods
+      .mintEdition()
+      .accountsPartial(accounts)
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    const editionAccounts = {
+      config: configPda,
+      editionMint: editionMint.publicKey,
+      editionMetadata: accounts.editionMetadata,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+    };
+    await program.methods
+      .claimEditionAuthority()
+      .accountsPartial({ ...editionAccounts, updateAuthority: minter.publicKey })
+      .signers([minter])
+      .rpc();
+    expect(
+      (await fetchMetadata(editionMint.publicKey)).updateAuthority.toBase58()
+    ).to.equal(configPda.toBase58());
+
+    const uri = "https://example.com/founder-migrated.json";
+    await program.methods
+      .updateEditionUri(uri)
+      .accountsPartial({ ...editionAccounts, authority })
+      .rpc();
+    expect((await fetchMetadata(editionMint.publicKey)).uri).to.equal(uri);
+  });
+
+  it("Sets the collection royalty on the collection metadata", async () => {
+    const accounts = {
+      config: configPda,
+      authority,
+      masterMetadata: metadataPda(masterMint),
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+    };
+    await program.methods.setCollectionRoyalty(750).accountsPartial(accounts).rpc();
+    expect((await fetchMetadata(masterMint)).sellerFeeBasisPoints).to.equal(750);
+
+    await expectError(
+      program.methods.setCollectionRoyalty(10_001).accountsPartial(accounts).rpc(),
+      "InvalidBasisPoints"
+    );
+    await program.methods.setCollectionRoyalty(500).accountsPartial(accounts).rpc();
+  });
+
+  it("Detects edition metadata drift after the URI is changed", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const accounts = mintAccounts(minter.publicKey, editionMint.publicKey);
+    const [receipt] = PublicKey.findProgramAddressSync(
+      [Buffer.from("receipt"), editionMint.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .mintWithMetadataHash(Array(32).fill(0))
+      .accountsPartial({ mint: accounts, authority, receipt, systemProgram: SystemProgram.programId })
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    const integrityAccounts = {
+      config: configPda,
+      editionMint: editionMint.publicKey,
+      editionMetadata: accounts.editionMetadata,
+      receipt,
+    };
+    await program.methods.verifyEditionIntegrity().accountsPartial(integrityAccounts).rpc();
+
+    // Later config changes are not drift: the receipt holds what this edition got
+    await program.methods
+      .updateMetadataConfig(editionName, editionSymbol, "https://example.com/next-drop.json")
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await program.methods.verifyEditionIntegrity().accountsPartial(integrityAccounts).rpc();
+    await program.methods
+      .updateMetadataConfig(editionName, editionSymbol, editionUri)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    const editionAccounts = {
+      config: configPda,
+      editionMint: editionMint.publicKey,
+      editionMetadata: accounts.editionMetadata,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+    };
+    await program.methods
+      .claimEditionAuthority()
+      .accountsPartial({ ...editionAccounts, updateAuthority: minter.publicKey })
+      .signers([minter])
+      .rpc();
+    await program.methods
+      .updateEditionUri("https://example.com/tampered.json")
+      .accountsPartial({ ...editionAccounts, authority })
+      .rpc();
+
+    await expectError(
+      program.methods.verifyEditionIntegrity().accountsPartial(integrityAccounts).rpc(),
+      "MetadataDrift"
+    );
+  });
+
+  it("Delegates new editions to the custodian for delegated freezes", async () => {
+    const custodian = await fundedWallet();
+    const update = { config: configPda, authority };
+    await program.methods
+      .updateCustodian(custodian.publicKey)
+      .accountsPartial(update)
+      .rpc();
+
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const accounts = mintAccounts(minter.publicKey, editionMint.publicKey);
+    await expectError(
+      program.methods
+        .mintEdition()
+        .accountsPartial(accounts)
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc(),
+      "InvalidCustodian"
+    );
+    await program.methods
+      .mintEdition()
+      .accountsPartial({ ...accounts, custodian: custodian.publicKey })
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    // The mint's freeze authority is the edition PDA; the custodian holds the delegation
+    const mint = (await provider.connection.getAccountInfo(editionMint.publicKey))!
+      .data;
+    expect(new PublicKey(mint.subarray(50, 82)).toBase58()).to.equal(
+      accounts.edition.toBase58()
+    );
+    const token = (await provider.connection.getAccountInfo(
+      accounts.editionTokenAccount
+    ))!.data;
+    expect(new PublicKey(token.subarray(76, 108)).toBase58()).to.equal(
+      custodian.publicKey.toBase58()
+    );
+
+    // FreezeDelegatedAccount, signed by the custodian rather than the minter
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        new TransactionInstruction({
+          programId: TOKEN_METADATA_PROGRAM_ID,
+          keys: [
+            { pubkey: custodian.publicKey, isSigner: true, isWritable: true },
+            { pubkey: accounts.editionTokenAccount, isSigner: false, isWritable: true },
+            { pubkey: accounts.edition, isSigner: false, isWritable: false },
+            { pubkey: editionMint.publicKey, isSigner: false, isWritable: false },
+            { pubkey: TOKEN_PROGRAM_ID, isSigner: false, isWritable: false },
+          ],
+          data: Buffer.from([26]),
+        })
+      ),
+      [custodian]
+    );
+    const frozen = (await provider.connection.getAccountInfo(
+      accounts.editionTokenAccount
+    ))!.data;
+    expect(frozen[108]).to.equal(2);
+
+    await program.methods.updateCustodian(null).accountsPartial(update).rpc();
+  });
+
+  it("Snapshots the edition count of two holders", async () => {
+    const epoch = new anchor.BN(1);
+    const holders = [await fundedWallet(), await fundedWallet()];
+    const holdings = [2, 1];
+
+    for (const [i, holder] of holders.entries()) {
+      const editionAccounts = [];
+      for (let n = 0; n < holdings[i]; n++) {
+        const editionMint = Keypair.generate();
+        const accounts = mintAccounts(holder.publicKey, editionMint.publicKey);
+        await program.methods
+          .mintEdition()
+          .accountsPartial(accounts)
+          .preInstructions(computeBudget)
+          .signers([holder, editionMint])
+          .rpc();
+        await verifyEdition(editionMint.publicKey);
+        editionAccounts.push(
+          { pubkey: accounts.editionTokenAccount, isSigner: false, isWritable: false },
+          { pubkey: accounts.editionMetadata, isSigner: false, isWritable: false }
+        );
+      }
+
+      await program.methods
+        .snapshotHolder(epoch)
+        .accountsPartial({
+          config: configPda,
+          authority,
+          holder: holder.publicKey,
+        })
+        .remainingAccounts(editionAccounts)
+        .rpc();
+    }
+
+    for (const [i, holder] of holders.entries()) {
+      const [snapshotPda] = PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("snapshot"),
+          epoch.toArrayLike(Buffer, "le", 8),
+          holder.publicKey.toBuffer(),
+        ],
+        program.programId
+      );
+      const snapshot = await program.account.holderSnapshot.fetch(snapshotPda);
+      expect(snapshot.holder.toBase58()).to.equal(holder.publicKey.toBase58());
+      expect(snapshot.editionCount.toNumber()).to.equal(holdings[i]);
+    }
+  });
+
+  it("Rejects mints once the end slot is reached", async () => {
+    const update = { config: configPda, authority };
+    const slot = await provider.connection.getSlot();
+    await program.methods
+      .updateEndSlot(new anchor.BN(slot + 2))
+      .accountsPartial(update)
+      .rpc();
+
+    // The local validator cannot warp, so wait for the cluster to pass the end slot
+    while ((await provider.connection.getSlot()) < slot + 2) {
+      await sleep(200);
+    }
+
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await expectError(
+      program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc(),
+      "MintEnded"
+    );
+
+    await program.methods.updateEndSlot(new anchor.BN(0)).accountsPartial(update).rpc();
+  });
+
+  it("Settles or cancels editions minted pending a fiat payment", async () => {
+    const pendingPda = (mint: PublicKey) =>
+      PublicKey.findProgramAddressSync(
+        [Buffer.from("pending"), mint.toBuffer()],
+        program.programId
+      )[0];
+    const tokenState = async (tokenAccount: PublicKey) =>
+      (await provider.connection.getAccountInfo(tokenAccount))!.data[108];
+
+    const mintPending = async () => {
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      const accounts = mintAccounts(minter.publicKey, editionMint.publicKey);
+      await program.methods
+        .mintPending()
+        .accountsPartial({ mint: accounts, authority })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+      expect(await tokenState(accounts.editionTokenAccount)).to.equal(2);
+      return {
+        config: configPda,
+        authority,
+        pending: pendingPda(editionMint.publicKey),
+        minter: minter.publicKey,
+        editionMint: editionMint.publicKey,
+        editionTokenAccount: accounts.editionTokenAccount,
+        edition: accounts.edition,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      };
+    };
+
+    const settled = await mintPending();
+    await program.methods.settleMint().accountsPartial(settled).rpc();
+    expect(await tokenState(settled.editionTokenAccount)).to.equal(1);
+    expect(await provider.connection.getAccountInfo(settled.pending)).to.be.null;
+
+    // Settling hands the close authority back to the minter; offsets 129-165 hold it
+    const settledToken = (await provider.connection.getAccountInfo(settled.editionTokenAccount))!;
+    expect(settledToken.data.readUInt32LE(129)).to.equal(0);
+
+    const cancelled = await mintPending();
+    const rent =
+      (await provider.connection.getBalance(cancelled.editionTokenAccount)) +
+      (await provider.connection.getBalance(cancelled.pending));
+    const minterBefore = await provider.connection.getBalance(cancelled.minter);
+    await program.methods.cancelPending().accountsPartial(cancelled).rpc();
+    expect(await provider.connection.getAccountInfo(cancelled.editionTokenAccount)).to.be.null;
+    expect(await provider.connection.getAccountInfo(cancelled.pending)).to.be.null;
+    expect((await provider.connection.getBalance(cancelled.minter)) - minterBefore).to.equal(rent);
+  });
+
+  it("Reconciles a drifted mint counter behind the admin-adjust flag", async () => {
+    const update = { config: configPda, authority };
+    const actual = (await program.account.config.fetch(configPda)).totalMinted;
+
+    await expectError(
+      program.methods.reconcileSupply(actual).accountsPartial(update).rpc(),
+      "AdminAdjustDisabled"
+    );
+
+    await program.methods.updateAdminAdjust(true).accountsPartial(update).rpc();
+    // Simulate drift, then correct it back to the verified count
+    await program.methods
+      .reconcileSupply(actual.addn(3))
+      .accountsPartial(update)
+      .rpc();
+    expect(
+      (await program.account.config.fetch(configPda)).totalMinted.toNumber()
+    ).to.equal(actual.toNumber() + 3);
+    await program.methods.reconcileSupply(actual).accountsPartial(update).rpc();
+    expect(
+      (await program.account.config.fetch(configPda)).totalMinted.toNumber()
+    ).to.equal(actual.toNumber());
+
+    // Going below the editions already numbered would hand those numbers out again
+    const { highestSequentialEdition, reservedMinted } = await program.account.config.fetch(configPda);
+    const floor = highestSequentialEdition.add(reservedMinted);
+    await expectError(
+      program.methods.reconcileSupply(floor.subn(1)).accountsPartial(update).rpc(),
+      "InvalidReconciledCount"
+    );
+
+    // Nor may it pass the supply cap
+    const supplyUpdate = { ...update, masterMetadata: metadataPda(masterMint) };
+    await program.methods.updateMaxSupply(actual.addn(2)).accountsPartial(supplyUpdate).rpc();
+    await expectError(
+      program.methods.reconcileSupply(actual.addn(3)).accountsPartial(update).rpc(),
+      "InvalidReconciledCount"
+    );
+    await program.methods.updateMaxSupply(new anchor.BN(0)).accountsPartial(supplyUpdate).rpc();
+
+    await program.methods.updateAdminAdjust(false).accountsPartial(update).rpc();
+  });
+
+  it("Applies rarity tier royalties by edition number", async () => {
+    const update = { config: configPda, authority };
+    const next = (await program.account.config.fetch(configPda)).totalMinted.addn(1);
+    await program.methods
+      .updateRarityTiers([
+        { maxEdition: next, sellerFeeBasisPoints: 1000 },
+        { maxEdition: next.addn(1), sellerFeeBasisPoints: 250 },
+      ])
+      .accountsPartial(update)
+      .rpc();
+
+    const minter = await fundedWallet();
+    const royalties = [];
+    for (let i = 0; i < 2; i++) {
+      const editionMint = Keypair.generate();
+      await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+      royalties.push(
+        (await fetchMetadata(editionMint.publicKey)).sellerFeeBasisPoints
+      );
+    }
+    expect(royalties).to.deep.equal([1000, 250]);
+
+    await program.methods.updateRarityTiers([]).accountsPartial(update).rpc();
+  });
+
+  it("Mints to a user who authorized a relayer off-chain", async () => {
+    const relayer = await fundedWallet();
+    const user = Keypair.generate();
+    const editionMint = Keypair.generate();
+    const accounts = {
+      mint: {
+        ...mintAccounts(relayer.publicKey, editionMint.publicKey),
+        editionTokenAccount: anchor.utils.token.associatedAddress({
+          mint: editionMint.publicKey,
+          owner: user.publicKey,
+        }),
+      },
+      user: user.publicKey,
+      instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+    };
+
+    await expectError(
+      program.methods
+        .mintRelayed()
+        .accountsPartial(accounts)
+        .preInstructions(computeBudget)
+        .signers([relayer, editionMint])
+        .rpc(),
+      "InvalidRelayAuthorization"
+    );
+
+    const authorization = Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: user.secretKey,
+      message: editionMint.publicKey.toBuffer(),
+    });
+    await program.methods
+      .mintRelayed()
+      .accountsPartial(accounts)
+      .preInstructions([...computeBudget, authorization])
+      .signers([relayer, editionMint])
+      .rpc();
+
+    const balance = await provider.connection.getTokenAccountBalance(
+      accounts.mint.editionTokenAccount
+    );
+    expect(balance.value.uiAmount).to.equal(1);
+  });
+
+  it("Counts a mint only when the vault receives exactly the price", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const { mintPrice } = await program.account.config.fetch(configPda);
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    // A shortfall would abort with PaymentNotReceived before total_minted moves,
+    // so a landed mint moves the vault by exactly the price
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(
+      vaultBefore + mintPrice.toNumber()
+    );
+  });
+
+  it("Stops minting at the max supply and only lets the cap rise", async () => {
+    const update = { config: configPda, authority, masterMetadata: metadataPda(masterMint) };
+    const minted = (await program.account.config.fetch(configPda)).totalMinted;
+    await program.methods
+      .updateMaxSupply(minted.addn(1))
+      .accountsPartial(update)
+      .rpc();
+
+    const minter = await fundedWallet();
+    const mint = (editionMint: Keypair) =>
+      program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc({ commitment: "confirmed" });
+    const soldOut = () => program.methods.isSoldOut().accountsPartial({ config: configPda }).view();
+    expect(await soldOut()).to.equal(false);
+    const signature = await mint(Keypair.generate());
+    expect(await soldOut()).to.equal(true);
+
+    // The last edition pauses the drop and announces it sold out
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const events = [...parser.parseLogs(tx!.meta!.logMessages!)];
+    const soldOutEvent = events.find((event) => event.name === "soldOut");
+    expect(soldOutEvent!.data.totalMinted.toString()).to.equal(minted.addn(1).toString());
+    expect((await program.account.config.fetch(configPda)).paused).to.equal(true);
+    await expectError(mint(Keypair.generate()), "MintingPaused");
+    await program.methods.setPaused(false).accountsPartial({ config: configPda, authority }).rpc();
+    await expectError(mint(Keypair.generate()), "MaxSupplyReached");
+
+    await expectError(
+      program.methods.updateMaxSupply(minted).accountsPartial(update).rpc(),
+      "InvalidMaxSupply"
+    );
+    await program.methods
+      .updateMaxSupply(new anchor.BN(0))
+      .accountsPartial(update)
+      .rpc();
+    // An unlimited supply never sells out
+    expect(await soldOut()).to.equal(false);
+  });
+
+  it("Escrows a vested edition for a non-signing recipient until its vesting end", async () => {
+    const recipient = await fundedWallet(1);
+    const editionMint = Keypair.generate();
+    const [vesting] = PublicKey.findProgramAddressSync(
+      [Buffer.from("vesting"), editionMint.publicKey.toBuffer()],
+      program.programId
+    );
+    const escrowTokenAccount = anchor.utils.token.associatedAddress({
+      mint: editionMint.publicKey,
+      owner: vesting,
+    });
+    const chainTime = async () =>
+      (await provider.connection.getBlockTime(
+        await provider.connection.getSlot()
+      ))!;
+    const vestingEnd = (await chainTime()) + 4;
+
+    // The authority mints and pays; only the edition mint signs alongside it
+    await program.methods
+      .mintVested(new anchor.BN(vestingEnd))
+      .accountsPartial({
+        mint: {
+          ...mintAccounts(authority, editionMint.publicKey),
+          editionTokenAccount: escrowTokenAccount,
+        },
+        authority,
+        recipient: recipient.publicKey,
+        vesting,
+      })
+      .preInstructions(computeBudget)
+      .signers([editionMint])
+      .rpc();
+
+    const recipientTokenAccount = anchor.utils.token.associatedAddress({
+      mint: editionMint.publicKey,
+      owner: recipient.publicKey,
+    });
+    const claim = () =>
+      program.methods
+        .claimVested()
+        .accountsPartial({
+          recipient: recipient.publicKey,
+          vesting,
+          editionMint: editionMint.publicKey,
+          escrowTokenAccount,
+          recipientTokenAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([recipient])
+        .rpc();
+    await expectError(claim(), "VestingNotEnded");
+    const escrowed = await provider.connection.getTokenAccountBalance(escrowTokenAccount);
+    expect(escrowed.value.uiAmount).to.equal(1);
+
+    while ((await chainTime()) < vestingEnd) {
+      await sleep(500);
+    }
+    await claim();
+    const claimed = await provider.connection.getTokenAccountBalance(recipientTokenAccount);
+    expect(claimed.value.uiAmount).to.equal(1);
+    expect(await provider.connection.getAccountInfo(escrowTokenAccount)).to.be.null;
+    expect(await provider.connection.getAccountInfo(vesting)).to.be.null;
+  });
+
+  it("Emits an EditionMinted event with the real mint values", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const { mintPrice } = await program.account.config.fetch(configPda);
+
+    let listener: number;
+    const minted = new Promise<any>((resolve) => {
+      listener = program.addEventListener("editionMinted", (event) => {
+        if (event.mint.equals(editionMint.publicKey)) resolve(event);
+      });
+    });
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+    const event = await minted;
+    await program.removeEventListener(listener!);
+
+    const { totalMinted } = await program.account.config.fetch(configPda);
+    expect(event.minter.toBase58()).to.equal(minter.publicKey.toBase58());
+    expect(event.editionNumber.toNumber()).to.equal(totalMinted.toNumber());
+    expect(event.price.toNumber()).to.equal(mintPrice.toNumber());
+    expect(event.discounted).to.be.false;
+  });
+
+  it("Rejects mints while paused and resumes after unpausing", async () => {
+    const update = { config: configPda, authority };
+    await program.methods.setPaused(true).accountsPartial(update).rpc();
+
+    const minter = await fundedWallet();
+    const mint = (method: "mintEdition" | "mintDiscounted") => {
+      const editionMint = Keypair.generate();
+      const builder =
+        method === "mintDiscounted"
+          ? program.methods.mintDiscounted([])
+          : program.methods.mintEdition();
+      return builder
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+    await expectError(mint("mintEdition"), "MintingPaused");
+    await expectError(mint("mintDiscounted"), "MintingPaused");
+
+    await program.methods.setPaused(false).accountsPartial(update).rpc();
+    await mint("mintEdition");
+  });
+
+  it("Prints numbered editions from a program-held print master", async () => {
+    const printMaster = Keypair.generate();
+    const masterTokenAccount = anchor.utils.token.associatedAddress({
+      mint: printMaster.publicKey,
+      owner: configPda,
+    });
+    await program.methods
+      .createPrintMaster(new anchor.BN(2))
+      .accountsPartial({
+        config: configPda,
+        authority,
+        printMasterMint: printMaster.publicKey,
+        masterTokenAccount,
+        printMasterMetadata: metadataPda(printMaster.publicKey),
+        printMasterEdition: editionPda(printMaster.publicKey),
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+        rent: SYSVAR_RENT_PUBKEY,
+      })
+      .preInstructions(computeBudget)
+      .signers([printMaster])
+      .rpc();
+
+    // Prints 1 and 2 share marker "0" (edition / 248)
+    const [editionMarker] = PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        printMaster.publicKey.toBuffer(),
+        Buffer.from("edition"),
+        Buffer.from("0"),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    );
+    const minter = await fundedWallet();
+    for (const number of [1, 2]) {
+      const printMint = Keypair.generate();
+      await program.methods
+        .printEdition()
+        .accountsPartial({
+          config: configPda,
+          minter: minter.publicKey,
+          paymentVault,
+          printMasterMint: printMaster.publicKey,
+          masterTokenAccount,
+          printMasterMetadata: metadataPda(printMaster.publicKey),
+          printMasterEdition: editionPda(printMaster.publicKey),
+          editionMarker,
+          printMint: printMint.publicKey,
+          printTokenAccount: anchor.utils.token.associatedAddress({
+            mint: printMint.publicKey,
+            owner: minter.publicKey,
+          }),
+          printMetadata: metadataPda(printMint.publicKey),
+          printEdition: editionPda(printMint.publicKey),
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+          priceFeed: null,
+          minterRecord: minterRecordPda(minter.publicKey),
+          holderTokenAccount: null,
+          holderMetadata: null,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, printMint])
+        .rpc();
+
+      // Edition account: key, parent master edition, edition number
+      const edition = (await provider.connection.getAccountInfo(
+        editionPda(printMint.publicKey)
+      ))!.data;
+      expect(new PublicKey(edition.subarray(1, 33)).toBase58()).to.equal(
+        editionPda(printMaster.publicKey).toBase58()
+      );
+      expect(Number(edition.readBigUInt64LE(33))).to.equal(number);
+    }
+
+    const marker = await provider.connection.getAccountInfo(editionMarker);
+    expect(marker!.owner.toBase58()).to.equal(TOKEN_METADATA_PROGRAM_ID.toBase58());
+  });
+
+  it("Caps mints per wallet with a minter record", async () => {
+    const update = { config: configPda, authority };
+    await program.methods
+      .updateWalletLimit(new anchor.BN(2))
+      .accountsPartial(update)
+      .rpc();
+
+    const mint = (minter: Keypair) => {
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+    const bot = await fundedWallet();
+    await mint(bot);
+    await mint(bot);
+    await expectError(mint(bot), "WalletLimitReached");
+    expect(
+      (
+        await program.account.minterRecord.fetch(minterRecordPda(bot.publicKey))
+      ).count.toNumber()
+    ).to.equal(2);
+
+    await mint(await fundedWallet());
+
+    await program.methods
+      .updateWalletLimit(new anchor.BN(0))
+      .accountsPartial(update)
+      .rpc();
+  });
+
+  it("Accepts SPL token payments and withdraws them", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const minterTokenAccount = anchor.utils.token.associatedAddress({
+      mint: paymentMint,
+      owner: minter.publicKey,
+    });
+    const authorityTokenAccount = anchor.utils.token.associatedAddress({
+      mint: paymentMint,
+      owner: authority,
+    });
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAtaIx(paymentMint, minter.publicKey),
+        createAtaIx(paymentMint, authority),
+        mintToIx(paymentMint, minterTokenAccount, tokenPrice.toNumber())
+      )
+    );
+
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    await program.methods
+      .mintWithToken()
+      .accountsPartial({
+        mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+        paymentMint,
+        minterTokenAccount,
+        tokenVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SystemProgram.programId,
+      })
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    const vaultTokens = await provider.connection.getTokenAccountBalance(tokenVault);
+    expect(vaultTokens.value.amount).to.equal(tokenPrice.toString());
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(vaultBefore);
+
+    const holding = await provider.connection.getTokenAccountBalance(
+      anchor.utils.token.associatedAddress({
+        mint: editionMint.publicKey,
+        owner: minter.publicKey,
+      })
+    );
+    expect(holding.value.amount).to.equal("1");
+
+    await program.methods
+      .withdrawTokens(tokenPrice)
+      .accountsPartial({
+        config: configPda,
+        authority,
+        tokenVault,
+        destination: authorityTokenAccount,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+    const withdrawn = await provider.connection.getTokenAccountBalance(authorityTokenAccount);
+    expect(withdrawn.value.amount).to.equal(tokenPrice.toString());
+  });
+
+  it("Disables SOL and token mints independently", async () => {
+    const update = { config: configPda, authority };
+    const minter = await fundedWallet();
+    const minterTokenAccount = anchor.utils.token.associatedAddress({
+      mint: paymentMint,
+      owner: minter.publicKey,
+    });
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAtaIx(paymentMint, minter.publicKey),
+        mintToIx(paymentMint, minterTokenAccount, tokenPrice.toNumber())
+      )
+    );
+    const mintWithSol = () => {
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+    const mintWithToken = () => {
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintWithToken()
+        .accountsPartial({
+          mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+          paymentMint,
+          minterTokenAccount,
+          tokenVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+
+    await program.methods.setSolMintEnabled(false).accountsPartial(update).rpc();
+    await expectError(mintWithSol(), "PaymentMethodDisabled");
+    await mintWithToken();
+
+    await program.methods.setSolMintEnabled(true).accountsPartial(update).rpc();
+    await program.methods.setTokenMintEnabled(false).accountsPartial(update).rpc();
+    await expectError(mintWithToken(), "PaymentMethodDisabled");
+    await mintWithSol();
+
+    await program.methods.setTokenMintEnabled(true).accountsPartial(update).rpc();
+    await program.methods
+      .withdrawTokens(tokenPrice)
+      .accountsPartial({
+        config: configPda,
+        authority,
+        tokenVault,
+        destination: anchor.utils.token.associatedAddress({ mint: paymentMint, owner: authority }),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  });
+
+  it("Takes a split SOL and token payment in one mint", async () => {
+    const update = { config: configPda, authority };
+    const solPart = new anchor.BN(100_000_000);
+    const tokenPart = new anchor.BN(50);
+    const minter = await fundedWallet();
+    const minterTokenAccount = anchor.utils.token.associatedAddress({
+      mint: paymentMint,
+      owner: minter.publicKey,
+    });
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAtaIx(paymentMint, minter.publicKey),
+        mintToIx(paymentMint, minterTokenAccount, tokenPart.toNumber())
+      )
+    );
+    const mintSplit = () => {
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintSplitPayment()
+        .accountsPartial({
+          mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+          paymentMint,
+          minterTokenAccount,
+          tokenVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+    await expectError(mintSplit(), "SplitPaymentDisabled");
+
+    await program.methods.updateSplitPricing(solPart, tokenPart).accountsPartial(update).rpc();
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    const tokensBefore = new anchor.BN(
+      (await provider.connection.getTokenAccountBalance(tokenVault)).value.amount
+    );
+    await mintSplit();
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(
+      vaultBefore + solPart.toNumber()
+    );
+    const tokensAfter = (await provider.connection.getTokenAccountBalance(tokenVault)).value.amount;
+    expect(tokensAfter).to.equal(tokensBefore.add(tokenPart).toString());
+
+    // The token half is spent, so the whole mint fails and no lamports move
+    const vaultAfter = await provider.connection.getBalance(paymentVault);
+    let failed = false;
+    try {
+      await mintSplit();
+    } catch {
+      failed = true;
+    }
+    expect(failed).to.equal(true);
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(vaultAfter);
+
+    await program.methods
+      .updateSplitPricing(new anchor.BN(0), new anchor.BN(0))
+      .accountsPartial(update)
+      .rpc();
+    await program.methods
+      .withdrawTokens(tokenPart)
+      .accountsPartial({
+        config: configPda,
+        authority,
+        tokenVault,
+        destination: anchor.utils.token.associatedAddress({ mint: paymentMint, owner: authority }),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+  });
+
+  it("Mints an edition into a PDA recipient", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const [smartWallet] = PublicKey.findProgramAddressSync(
+      [Buffer.from("smart_wallet"), minter.publicKey.toBuffer()],
+      SystemProgram.programId
+    );
+    const recipientTokenAccount = anchor.utils.token.associatedAddress({
+      mint: editionMint.publicKey,
+      owner: smartWallet,
+    });
+
+    await program.methods
+      .mintToPda(smartWallet)
+      .accountsPartial({
+        mint: {
+          ...mintAccounts(minter.publicKey, editionMint.publicKey),
+          editionTokenAccount: recipientTokenAccount,
+        },
+        recipient: smartWallet,
+      })
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    const holding = await provider.connection.getTokenAccountBalance(recipientTokenAccount);
+    expect(holding.value.amount).to.equal("1");
+  });
+
+  it("Verifies a minted edition into the master collection", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+    const before = await fetchMetadata(editionMint.publicKey);
+    expect(before.collection?.verified).to.equal(false);
+
+    await program.methods
+      .verifyCollection()
+      .accountsPartial({
+        config: configPda,
+        authority,
+        editionMint: editionMint.publicKey,
+        editionMetadata: metadataPda(editionMint.publicKey),
+        masterMint,
+        masterMetadata: metadataPda(masterMint),
+        masterEdition: editionPda(masterMint),
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .rpc();
+
+    const after = await fetchMetadata(editionMint.publicKey);
+    expect(after.collection?.verified).to.equal(true);
+    expect(after.collection?.key.toBase58()).to.equal(masterMint.toBase58());
+  });
+
+  it("Gates mints on a verified collection NFT with the expected update authority", async () => {
+    const holder = await fundedWallet(3);
+    const genuineGate = Keypair.generate();
+    const spoofedGate = Keypair.generate();
+    for (const gate of [genuineGate, spoofedGate]) {
+      await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(holder.publicKey, gate.publicKey))
+        .preInstructions(computeBudget)
+        .signers([holder, gate])
+        .rpc();
+    }
+    // Only the genuine gate is verified; the spoofed one names the collection unverified
+    await program.methods
+      .verifyCollection()
+      .accountsPartial({
+        config: configPda,
+        authority,
+        editionMint: genuineGate.publicKey,
+        editionMetadata: metadataPda(genuineGate.publicKey),
+        masterMint,
+        masterMetadata: metadataPda(masterMint),
+        masterEdition: editionPda(masterMint),
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .rpc();
+    await program.methods
+      .updateGate(masterMint, holder.publicKey)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    const gatedMint = (gate: PublicKey, editionMint: Keypair) =>
+      program.methods
+        .mintGated()
+        .accountsPartial({
+          mint: mintAccounts(holder.publicKey, editionMint.publicKey),
+          gateTokenAccount: anchor.utils.token.associatedAddress({
+            mint: gate,
+            owner: holder.publicKey,
+          }),
+          gateMetadata: metadataPda(gate),
+        })
+        .preInstructions(computeBudget)
+        .signers([holder, editionMint])
+        .rpc();
+
+    await expectError(gatedMint(spoofedGate.publicKey, Keypair.generate()), "InvalidGateNft");
+    const editionMint = Keypair.generate();
+    await gatedMint(genuineGate.publicKey, editionMint);
+    const holding = await provider.connection.getTokenAccountBalance(
+      anchor.utils.token.associatedAddress({
+        mint: editionMint.publicKey,
+        owner: holder.publicKey,
+      })
+    );
+    expect(holding.value.amount).to.equal("1");
+
+    await program.methods
+      .updateGate(null, PublicKey.default)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Rejects mints whose discounted price falls below the floor", async () => {
+    const { mintPrice, discountedPrice } = await program.account.config.fetch(configPda);
+    // Floor sits between the discounted and regular prices
+    await program.methods
+      .updateMinEffectivePrice(discountedPrice.addn(1))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    expect(mintPrice.gt(discountedPrice.addn(1))).to.equal(true);
+
+    const minter = await fundedWallet();
+    const discountedMint = Keypair.generate();
+    await expectError(
+      program.methods
+        .mintDiscounted([])
+        .accountsPartial(mintAccounts(minter.publicKey, discountedMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, discountedMint])
+        .rpc(),
+      "PriceTooLow"
+    );
+
+    const regularMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, regularMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, regularMint])
+      .rpc();
+
+    await program.methods
+      .updateMinEffectivePrice(new anchor.BN(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Uses the configured symbol for new editions", async () => {
+    await expectError(
+      program.methods
+        .updateMetadataConfig(editionName, "TOOLONGSYMBOL", editionUri)
+        .accountsPartial({ config: configPda, authority })
+        .rpc(),
+      "InvalidMetadataConfig"
+    );
+    await program.methods
+      .updateMetadataConfig(editionName, "AMMO2", editionUri)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    const metadata = await fetchMetadata(editionMint.publicKey);
+    expect(metadata.symbol).to.equal("AMMO2");
+    expect(metadata.name.startsWith(`${editionName} #`)).to.equal(true);
+
+    await program.methods
+      .updateMetadataConfig(editionName, editionSymbol, editionUri)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Keeps minter records open until the drop is finalized", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    await expectError(
+      program.methods
+        .closeMinterRecord()
+        .accountsPartial({
+          config: configPda,
+          minter: minter.publicKey,
+          minterRecord: minterRecordPda(minter.publicKey),
+        })
+        .signers([minter])
+        .rpc(),
+      "DropNotFinalized"
+    );
+  });
+
+  it("Pays edition royalties to the configured recipient", async () => {
+    // The recipient must be the collection's primary creator, the provider wallet
+    const update = { config: configPda, authority, masterMetadata: metadataPda(masterMint) };
+    await expectError(
+      program.methods.updateRoyalties(10_001, authority).accountsPartial(update).rpc(),
+      "InvalidBasisPoints"
+    );
+    await program.methods.updateRoyalties(750, authority).accountsPartial(update).rpc();
+
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    const metadata = await fetchMetadata(editionMint.publicKey);
+    expect(metadata.sellerFeeBasisPoints).to.equal(750);
+    expect(
+      metadata.creators.map((creator) => ({
+        address: creator.address.toBase58(),
+        verified: creator.verified,
+        share: creator.share,
+      }))
+    ).to.deep.equal([
+      { address: authority.toBase58(), verified: false, share: 100 },
+      { address: minter.publicKey.toBase58(), verified: true, share: 0 },
+    ]);
+
+    await program.methods.updateRoyalties(500, authority).accountsPartial(update).rpc();
+  });
+
+  it("Caps royalties at the authority-set ceiling", async () => {
+    const update = { config: configPda, authority, masterMetadata: metadataPda(masterMint) };
+    const ceiling = (bps: number) =>
+      program.methods.updateMaxRoyalty(bps).accountsPartial({ config: configPda, authority }).rpc();
+    await ceiling(750);
+    await expectError(
+      program.methods.updateRoyalties(751, authority).accountsPartial(update).rpc(),
+      "RoyaltyExceedsCeiling"
+    );
+    await program.methods.updateRoyalties(750, authority).accountsPartial(update).rpc();
+    expect((await program.account.config.fetch(configPda)).royaltyBasisPoints).to.equal(750);
+
+    // The ceiling can't drop below the royalty already configured
+    await expectError(ceiling(700), "RoyaltyExceedsCeiling");
+
+    await program.methods.updateRoyalties(500, authority).accountsPartial(update).rpc();
+    await ceiling(10_000);
+  });
+
+  it("Requires the royalty recipient to be the collection's primary creator", async () => {
+    const update = { config: configPda, authority, masterMetadata: metadataPda(masterMint) };
+    await expectError(
+      program.methods
+        .updateRoyalties(500, Keypair.generate().publicKey)
+        .accountsPartial(update)
+        .rpc(),
+      "MissingCollectionCreator"
+    );
+    await program.methods.updateRoyalties(500, authority).accountsPartial(update).rpc();
+    expect(
+      (await program.account.config.fetch(configPda)).royaltyRecipient.toBase58()
+    ).to.equal(authority.toBase58());
+  });
+
+  it("Transfers authority in two steps", async () => {
+    const newAuthority = await fundedWallet(1);
+    const impostor = await fundedWallet(1);
+
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await program.methods
+      .cancelAuthorityTransfer()
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    expect((await program.account.config.fetch(configPda)).pendingAuthority).to.equal(null);
+
+    await program.methods
+      .proposeAuthority(newAuthority.publicKey)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await expectError(
+      program.methods
+        .acceptAuthority()
+        .accountsPartial({ config: configPda, pendingAuthority: impostor.publicKey })
+        .signers([impostor])
+        .rpc(),
+      "NotPendingAuthority"
+    );
+    await program.methods
+      .acceptAuthority()
+      .accountsPartial({ config: configPda, pendingAuthority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    let config = await program.account.config.fetch(configPda);
+    expect(config.authority.toBase58()).to.equal(newAuthority.publicKey.toBase58());
+    expect(config.pendingAuthority).to.equal(null);
+
+    // Hand control back to the provider wallet for the remaining tests
+    await program.methods
+      .proposeAuthority(authority)
+      .accountsPartial({ config: configPda, authority: newAuthority.publicKey })
+      .signers([newAuthority])
+      .rpc();
+    await program.methods
+      .acceptAuthority()
+      .accountsPartial({ config: configPda, pendingAuthority: authority })
+      .rpc();
+    config = await program.account.config.fetch(configPda);
+    expect(config.authority.toBase58()).to.equal(authority.toBase58());
+  });
+
+  it("Records a reproducible serial hash per edition", async () => {
+    const metadataHash = Array.from(
+      Buffer.from(anchor.utils.sha256.hash("edition metadata json"), "hex")
+    );
+    const serials: string[] = [];
+    for (let i = 0; i < 2; i++) {
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      const [receipt] = PublicKey.findProgramAddressSync(
+        [Buffer.from("receipt"), editionMint.publicKey.toBuffer()],
+        program.programId
+      );
+      await program.methods
+        .mintWithMetadataHash(metadataHash)
+        .accountsPartial({
+          mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+          authority,
+          receipt,
+          systemProgram: SystemProgram.programId,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+
+      const { editionNumber, serialHash } = await program.account.mintReceipt.fetch(receipt);
+      const expected = createHash("sha256")
+        .update(masterMint.toBuffer())
+        .update(editionNumber.toArrayLike(Buffer, "le", 8))
+        .digest("hex");
+      expect(Buffer.from(serialHash).toString("hex")).to.equal(expected);
+      serials.push(expected);
+    }
+    expect(serials[0]).to.not.equal(serials[1]);
+  });
+
+  it("Rejects mints outside the mint window", async () => {
+    const update = { config: configPda, authority };
+    const now = Math.floor(Date.now() / 1000);
+    const minter = await fundedWallet();
+    const mint = () => {
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+
+    await expectError(
+      program.methods
+        .updateMintWindow(new anchor.BN(now + 10), new anchor.BN(now))
+        .accountsPartial(update)
+        .rpc(),
+      "InvalidMintWindow"
+    );
+
+    // A window opening in an hour rejects early mints
+    await program.methods
+      .updateMintWindow(new anchor.BN(now + 3600), new anchor.BN(0))
+      .accountsPartial(update)
+      .rpc();
+    await expectError(mint(), "MintNotStarted");
+
+    // A window that closed an hour ago rejects late mints
+    await program.methods
+      .updateMintWindow(new anchor.BN(now - 7200), new anchor.BN(now - 3600))
+      .accountsPartial(update)
+      .rpc();
+    await expectError(mint(), "MintEnded");
+
+    await program.methods
+      .updateMintWindow(new anchor.BN(now - 3600), new anchor.BN(now + 3600))
+      .accountsPartial(update)
+      .rpc();
+    await mint();
+
+    await program.methods
+      .updateMintWindow(new anchor.BN(0), new anchor.BN(0))
+      .accountsPartial(update)
+      .rpc();
+  });
+
+  it("Restricts discounted mints to the Merkle allowlist", async () => {
+    const update = { config: configPda, authority };
+    const members = [await fundedWallet(), await fundedWallet(), await fundedWallet()];
+    const outsider = await fundedWallet();
+
+    // Sorted-pair keccak tree over keccak(wallet) leaves; odd nodes carry up unchanged
+    const hashPair = (a: Buffer, b: Buffer) =>
+      Buffer.from(keccak_256(Buffer.compare(a, b) <= 0 ? Buffer.concat([a, b]) : Buffer.concat([b, a])));
+    const leaves = members.map((wallet) => Buffer.from(keccak_256(wallet.publicKey.toBuffer())));
+    const levels = [leaves];
+    while (levels[levels.length - 1].length > 1) {
+      const level = levels[levels.length - 1];
+      const next: Buffer[] = [];
+      for (let i = 0; i < level.length; i += 2) {
+        next.push(i + 1 < level.length ? hashPair(level[i], level[i + 1]) : level[i]);
+      }
+      levels.push(next);
+    }
+    const root = levels[levels.length - 1][0];
+    const proofFor = (index: number) => {
+      const proof: number[][] = [];
+      for (const level of levels.slice(0, -1)) {
+        const sibling = index ^ 1;
+        if (sibling < level.length) proof.push(Array.from(level[sibling]));
+        index >>= 1;
+      }
+      return proof;
+    };
+
+    await program.methods.setAllowlistRoot(Array.from(root)).accountsPartial(update).rpc();
+
+    const allowlistStatus = (wallet: PublicKey) =>
+      PublicKey.findProgramAddressSync([Buffer.from("allowlist"), wallet.toBuffer()], program.programId)[0];
+    const mintDiscounted = (minter: Keypair, proof: number[][], status: PublicKey | null = null) => {
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintDiscounted(proof)
+        .accountsPartial({
+          ...mintAccounts(minter.publicKey, editionMint.publicKey),
+          allowlistStatus: status,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+    await mintDiscounted(members[1], proofFor(1));
+    await expectError(mintDiscounted(outsider, proofFor(1)), "NotAllowlisted");
+
+    // Register once, then mint repeatedly without a proof
+    const register = (minter: Keypair, proof: number[][]) =>
+      program.methods
+        .registerAllowlist(proof)
+        .accountsPartial({
+          config: configPda,
+          allowlistStatus: allowlistStatus(minter.publicKey),
+          minter: minter.publicKey,
+          systemProgram: SystemProgram.programId,
+        })
+        .signers([minter])
+        .rpc();
+    await register(members[0], proofFor(0));
+    const status = await program.account.allowlistStatus.fetch(allowlistStatus(members[0].publicKey));
+    expect(status.minter.toBase58()).to.equal(members[0].publicKey.toBase58());
+    expect(Buffer.from(status.merkleRoot).equals(root)).to.equal(true);
+    await mintDiscounted(members[0], [], allowlistStatus(members[0].publicKey));
+    await mintDiscounted(members[0], [], allowlistStatus(members[0].publicKey));
+    await expectError(register(outsider, proofFor(0)), "NotAllowlisted");
+
+    // A new root invalidates the registration until the wallet registers again
+    const otherRoot = Array.from(hashPair(root, root));
+    await program.methods.setAllowlistRoot(otherRoot).accountsPartial(update).rpc();
+    await expectError(
+      mintDiscounted(members[0], [], allowlistStatus(members[0].publicKey)),
+      "NotAllowlisted"
+    );
+
+    await program.methods.setAllowlistRoot(Array(32).fill(0)).accountsPartial(update).rpc();
+  });
+
+  it("Charges the USD-pegged price through the Pyth feed", async () => {
+    const update = { config: configPda, authority };
+    // Mock feed from Anchor.toml: SOL at $150 +/- $0.05, published at unix time 1700000000
+    const priceFeed = new PublicKey("2GR5bZdxLnAKdtr4W1KjXXxm4z8LMEDkbkqAegsh31WD");
+    const solUsdFeedId = Array.from(
+      Buffer.from("ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d", "hex")
+    );
+    const feedAge = Math.floor(Date.now() / 1000) - 1_700_000_000;
+
+    const minter = await fundedWallet();
+    const mint = (feed: PublicKey | null) => {
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintEdition()
+        .accountsPartial({
+          ...mintAccounts(minter.publicKey, editionMint.publicKey),
+          priceFeed: feed,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+
+    // $45 at $150 per SOL is 0.3 SOL
+    const usdPricing = (maxPriceAge: number, feedId = solUsdFeedId, maxConfidenceBps = 10) =>
+      program.methods
+        .updateUsdPricing(
+          new anchor.BN(4500),
+          priceFeed,
+          new anchor.BN(maxPriceAge),
+          feedId,
+          maxConfidenceBps
+        )
+        .accountsPartial(update)
+        .rpc();
+    await usdPricing(feedAge + 3600);
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    await mint(priceFeed);
+    expect((await provider.connection.getBalance(paymentVault)) - vaultBefore).to.equal(
+      0.3 * LAMPORTS_PER_SOL
+    );
+    await expectError(mint(null), "StaleOracle");
+
+    await usdPricing(60);
+    await expectError(mint(priceFeed), "StaleOracle");
+
+    // Another feed's update, or a confidence of about 3.3 bps against a 1 bps cap
+    await usdPricing(feedAge + 3600, Array(32).fill(1));
+    await expectError(mint(priceFeed), "StaleOracle");
+    await usdPricing(feedAge + 3600, solUsdFeedId, 1);
+    await expectError(mint(priceFeed), "StaleOracle");
+
+    await program.methods
+      .updateUsdPricing(new anchor.BN(0), PublicKey.default, new anchor.BN(0), Array(32).fill(0), 0)
+      .accountsPartial(update)
+      .rpc();
+  });
+
+  it("Pre-validates a batch against remaining supply", async () => {
+    const update = { config: configPda, authority, masterMetadata: metadataPda(masterMint) };
+    const { totalMinted, mintPrice } = await program.account.config.fetch(configPda);
+    await program.methods.updateMaxSupply(totalMinted.addn(2)).accountsPartial(update).rpc();
+
+    const minter = await fundedWallet(5);
+    const accounts = {
+      config: configPda,
+      minter: minter.publicKey,
+      minterRecord: null,
+      priceFeed: null,
+      holderTokenAccount: null,
+      holderMetadata: null,
+    };
+    const cost = await program.methods
+      .validateBatch(new anchor.BN(2))
+      .accountsPartial(accounts)
+      .view();
+    expect(cost.toString()).to.equal(mintPrice.muln(2).toString());
+    await expectError(
+      program.methods.validateBatch(new anchor.BN(3)).accountsPartial(accounts).rpc(),
+      "MaxSupplyReached"
+    );
+    // Held to the same batch size as mint_batch
+    await expectError(
+      program.methods.validateBatch(new anchor.BN(6)).accountsPartial(accounts).rpc(),
+      "MintBatchTooLarge"
+    );
+
+    await program.methods.updateMaxSupply(new anchor.BN(0)).accountsPartial(update).rpc();
+  });
+
+  it("Leaves no wallet able to mint a second edition token", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    // Mint authority now sits with the edition PDA
+    const mintData = (await provider.connection.getAccountInfo(editionMint.publicKey))!.data;
+    expect(mintData.readUInt32LE(0)).to.equal(1);
+    expect(new PublicKey(mintData.subarray(4, 36)).toBase58()).to.equal(
+      editionPda(editionMint.publicKey).toBase58()
+    );
+
+    const tokenAccount = anchor.utils.token.associatedAddress({
+      mint: editionMint.publicKey,
+      owner: minter.publicKey,
+    });
+    let failed = false;
+    try {
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          new TransactionInstruction({
+            programId: TOKEN_PROGRAM_ID,
+            keys: [
+              key(editionMint.publicKey, false, true),
+              key(tokenAccount, false, true),
+              key(minter.publicKey, true),
+            ],
+            data: Buffer.concat([Buffer.from([7]), u64(1)]),
+          })
+        ),
+        [minter]
+      );
+    } catch {
+      failed = true;
+    }
+    expect(failed).to.equal(true);
+    const holding = await provider.connection.getTokenAccountBalance(tokenAccount);
+    expect(holding.value.amount).to.equal("1");
+  });
+
+  it("Drains the vault down to its rent-exempt minimum", async () => {
+    const minter = await fundedWallet();
+    for (let i = 0; i < 2; i++) {
+      const editionMint = Keypair.generate();
+      await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    }
+
+    const rentFloor = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    const authorityBefore = await provider.connection.getBalance(authority);
+    const signature = await program.methods
+      .withdrawAll()
+      .accountsPartial({ config: configPda, authority, paymentVault })
+      .rpc({ commitment: "confirmed" });
+    const tx = await provider.connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(rentFloor);
+    expect(
+      (await provider.connection.getBalance(authority)) - authorityBefore + tx!.meta!.fee
+    ).to.equal(vaultBefore - rentFloor);
+    await expectError(
+      program.methods
+        .withdrawAll()
+        .accountsPartial({ config: configPda, authority, paymentVault })
+        .rpc(),
+      "NothingToWithdraw"
+    );
+  });
+
+  it("Caps withdrawals to the vested share of collected funds", async () => {
+    const update = { config: configPda, authority };
+    const withdrawAccounts = { config: configPda, authority, paymentVault };
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+
+    const rentFloor = await provider.connection.getMinimumBalanceForRentExemption(0);
+    const available = (await provider.connection.getBalance(paymentVault)) - rentFloor;
+    const now = Math.floor(Date.now() / 1000);
+
+    // One second into a year-long schedule almost nothing has vested
+    await program.methods
+      .updateWithdrawalVesting(new anchor.BN(now - 1), new anchor.BN(365 * 24 * 3600))
+      .accountsPartial(update)
+      .rpc();
+    await expectError(
+      program.methods.withdraw(new anchor.BN(available)).accountsPartial(withdrawAccounts).rpc(),
+      "ExceedsVested"
+    );
+
+    // A schedule that already ran out releases everything
+    await program.methods
+      .updateWithdrawalVesting(new anchor.BN(now - 7200), new anchor.BN(3600))
+      .accountsPartial(update)
+      .rpc();
+    await program.methods.withdraw(new anchor.BN(available)).accountsPartial(withdrawAccounts).rpc();
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(rentFloor);
+
+    await program.methods
+      .updateWithdrawalVesting(new anchor.BN(0), new anchor.BN(0))
+      .accountsPartial(update)
+      .rpc();
+  });
+
+  it("Rejects edition metadata and edition accounts that are not the mint's PDAs", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const decoy = Keypair.generate().publicKey;
+    const mint = (overrides: object) =>
+      program.methods
+        .mintEdition()
+        .accountsPartial({
+          ...mintAccounts(minter.publicKey, editionMint.publicKey),
+          ...overrides,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+
+    await expectError(mint({ editionMetadata: decoy }), "InvalidMetadataAccount");
+    await expectError(mint({ edition: decoy }), "InvalidMetadataAccount");
+    // Another mint's PDAs are just as wrong
+    await expectError(
+      mint({ editionMetadata: metadataPda(masterMint) }),
+      "InvalidMetadataAccount"
+    );
+  });
+
+  it("Charges the old price during the grace period after a raise", async () => {
+    const update = { config: configPda, authority };
+    const { mintPrice } = await program.account.config.fetch(configPda);
+    await program.methods.updatePriceGrace(new anchor.BN(600)).accountsPartial(update).rpc();
+    await program.methods
+      .updatePricing(mintPrice.muln(2), null, null)
+      .accountsPartial(update)
+      .rpc();
+
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+    expect((await provider.connection.getBalance(paymentVault)) - vaultBefore).to.equal(
+      mintPrice.toNumber()
+    );
+
+    await program.methods.updatePricing(mintPrice, null, null).accountsPartial(update).rpc();
+    await program.methods.updatePriceGrace(new anchor.BN(0)).accountsPartial(update).rpc();
+  });
+
+  it("Rejects withdrawals larger than the vault balance", async () => {
+    const vaultBalance = await provider.connection.getBalance(paymentVault);
+    await expectError(
+      program.methods
+        .withdraw(new anchor.BN(vaultBalance + 1))
+        .accountsPartial({ config: configPda, authority, paymentVault })
+        .rpc(),
+      "InsufficientVaultBalance"
+    );
+
+    const tokens = await provider.connection.getTokenAccountBalance(tokenVault);
+    await expectError(
+      program.methods
+        .withdrawTokens(new anchor.BN(tokens.value.amount).addn(1))
+        .accountsPartial({
+          config: configPda,
+          authority,
+          tokenVault,
+          destination: anchor.utils.token.associatedAddress({
+            mint: paymentMint,
+            owner: authority,
+          }),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc(),
+      "InsufficientVaultBalance"
+    );
+  });
+
+  it("Mints at the discounted price only for wallets with enough stake", async () => {
+    const stakingProgram = new PublicKey("7Yg1nF5Qw71FgyWXppCuL6gL2k17XHxKTept8mhG9gjX");
+    const stakeAbove = new PublicKey("H9CtmeVTb6dAkMtey37yn2QwUojeGeGLKimEscvrSF7X");
+    const stakeBelow = new PublicKey("DMEoLSawybJD5AsPANyXyUK5nM22SY7yK8v1e9u156xw");
+    const staker = Keypair.fromSecretKey(
+      Uint8Array.from(JSON.parse(readFileSync("tests/fixtures/staker.json", "utf8")))
+    );
+    await provider.connection.confirmTransaction(
+      await provider.connection.requestAirdrop(staker.publicKey, 2 * LAMPORTS_PER_SOL)
+    );
+    const mintForStakers = (minter: Keypair, stakeAccount: PublicKey, editionMint: Keypair) =>
+      program.methods
+        .mintForStakers()
+        .accountsPartial({
+          mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+          stakeAccount,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+
+    await expectError(mintForStakers(staker, stakeAbove, Keypair.generate()), "NotStaking");
+
+    // The fixtures are the staking program's StakeEntry accounts
+    const stakeDiscriminator = Array.from(
+      Buffer.from(anchor.utils.sha256.hash("account:StakeEntry"), "hex").subarray(0, 8)
+    );
+    await program.methods
+      .updateStaking(stakingProgram, new anchor.BN(100), Array(8).fill(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await expectError(mintForStakers(staker, stakeAbove, Keypair.generate()), "NotStaking");
+    await program.methods
+      .updateStaking(stakingProgram, new anchor.BN(100), stakeDiscriminator)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await expectError(mintForStakers(staker, stakeBelow, Keypair.generate()), "NotStaking");
+    await expectError(
+      mintForStakers(await fundedWallet(), stakeAbove, Keypair.generate()),
+      "NotStaking"
+    );
+
+    const { discountedPrice } = await program.account.config.fetch(configPda);
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    await mintForStakers(staker, stakeAbove, Keypair.generate());
+    expect((await provider.connection.getBalance(paymentVault)) - vaultBefore).to.equal(
+      discountedPrice.toNumber()
+    );
+
+    await program.methods
+      .updateStaking(null, new anchor.BN(0), Array(8).fill(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Hands out the free supply, then only paid mints remain", async () => {
+    const minter = await fundedWallet();
+    const mintWith = (method: "mintFree" | "mintEdition", editionMint: Keypair) =>
+      program.methods[method]()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+
+    await program.methods
+      .updateFreeSupply(new anchor.BN(2))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    await mintWith("mintFree", Keypair.generate());
+    await mintWith("mintFree", Keypair.generate());
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(vaultBefore);
+    const { freeMinted } = await program.account.config.fetch(configPda);
+    expect(freeMinted.toNumber()).to.equal(2);
+
+    await expectError(mintWith("mintFree", Keypair.generate()), "FreeSupplyExhausted");
+    await mintWith("mintEdition", Keypair.generate());
+    expect(await provider.connection.getBalance(paymentVault)).to.be.greaterThan(vaultBefore);
+
+    await program.methods
+      .updateFreeSupply(new anchor.BN(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Splits the payment with a supplied referrer", async () => {
+    const referrer = await fundedWallet(1);
+    const [referrerCode] = PublicKey.findProgramAddressSync(
+      [Buffer.from("referral"), Buffer.from("DIRECT")],
+      program.programId
+    );
+    await program.methods
+      .createReferralCode("DIRECT")
+      .accountsPartial({
+        referralCode: referrerCode,
+        owner: referrer.publicKey,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([referrer])
+      .rpc();
+    await program.methods
+      .updateReferralRate(1_000)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    const { mintPrice } = await program.account.config.fetch(configPda);
+    const cut = mintPrice.divn(10).toNumber();
+
+    const mintReferred = async (referred: PublicKey | null, minter?: Keypair) => {
+      minter ??= await fundedWallet();
+      const editionMint = Keypair.generate();
+      await program.methods
+        .mintEdition()
+        .accountsPartial({
+          ...mintAccounts(minter.publicKey, editionMint.publicKey),
+          referrer: referred,
+          referrerCode: referred && referrerCode,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+
+    let vaultBefore = await provider.connection.getBalance(paymentVault);
+    const referrerBefore = await provider.connection.getBalance(referrer.publicKey);
+    await mintReferred(referrer.publicKey);
+    expect(await provider.connection.getBalance(referrer.publicKey)).to.equal(referrerBefore + cut);
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(
+      vaultBefore + mintPrice.toNumber() - cut
+    );
+    const { mints, totalEarned } = await program.account.referralCode.fetch(referrerCode);
+    expect(mints.toNumber()).to.equal(1);
+    expect(totalEarned.toNumber()).to.equal(cut);
+
+    // Neither a wallet without a code nor the referrer minting for itself earns a cut
+    await expectError(mintReferred((await fundedWallet(1)).publicKey), "InvalidReferrer");
+    await expectError(mintReferred(referrer.publicKey, referrer), "InvalidReferrer");
+
+    vaultBefore = await provider.connection.getBalance(paymentVault);
+    await mintReferred(null);
+    expect(await provider.connection.getBalance(referrer.publicKey)).to.equal(referrerBefore + cut);
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(
+      vaultBefore + mintPrice.toNumber()
+    );
+
+    await program.methods
+      .updateReferralRate(0)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Returns a typed config view", async () => {
+    const view = await program.methods
+      .getConfig()
+      .accountsPartial({ config: configPda })
+      .view();
+    const config = await program.account.config.fetch(configPda);
+    expect(view.authority.toBase58()).to.equal(config.authority.toBase58());
+    expect(view.mintPrice.toString()).to.equal(config.mintPrice.toString());
+    expect(view.discountedPrice.toString()).to.equal(config.discountedPrice.toString());
+    expect(view.tokenPrice.toString()).to.equal(config.tokenPrice.toString());
+    expect(view.totalMinted.toString()).to.equal(config.totalMinted.toString());
+    expect(view.maxSupply.toString()).to.equal(config.maxSupply.toString());
+    expect(view.paused).to.equal(config.paused);
+  });
+
+  it("Asserts the payment vault derivation for a bump", async () => {
+    const [, vaultBump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("payment_vault")],
+      program.programId
+    );
+    await program.methods
+      .assertVault(vaultBump)
+      .accountsPartial({ paymentVault })
+      .rpc();
+    await expectError(
+      program.methods
+        .assertVault((vaultBump + 255) % 256)
+        .accountsPartial({ paymentVault })
+        .rpc(),
+      "InvalidVault"
+    );
+  });
+
+  it("Raises the wallet limit for verified collection holders", async () => {
+    const update = { config: configPda, authority };
+    await program.methods.updateWalletLimit(new anchor.BN(1)).accountsPartial(update).rpc();
+    await program.methods.updateHolderWalletLimit(new anchor.BN(3)).accountsPartial(update).rpc();
+
+    const holder = await fundedWallet(5);
+    const nonHolder = await fundedWallet();
+    const holderNft = Keypair.generate();
+    const mint = (minter: Keypair, editionMint: Keypair, withHolderNft: boolean) =>
+      program.methods
+        .mintEdition()
+        .accountsPartial({
+          ...mintAccounts(minter.publicKey, editionMint.publicKey),
+          holderTokenAccount: withHolderNft
+            ? anchor.utils.token.associatedAddress({ mint: holderNft.publicKey, owner: minter.publicKey })
+            : null,
+          holderMetadata: withHolderNft ? metadataPda(holderNft.publicKey) : null,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+
+    await mint(holder, holderNft, false);
+    await program.methods
+      .verifyCollection()
+      .accountsPartial({
+        config: configPda,
+        authority,
+        editionMint: holderNft.publicKey,
+        editionMetadata: metadataPda(holderNft.publicKey),
+        masterMint,
+        masterMetadata: metadataPda(masterMint),
+        masterEdition: editionPda(masterMint),
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      })
+      .rpc();
+    await mint(holder, Keypair.generate(), true);
+    await mint(holder, Keypair.generate(), true);
+    await expectError(mint(holder, Keypair.generate(), true), "WalletLimitReached");
+
+    await mint(nonHolder, Keypair.generate(), false);
+    await expectError(mint(nonHolder, Keypair.generate(), false), "WalletLimitReached");
+
+    await program.methods.updateHolderWalletLimit(new anchor.BN(0)).accountsPartial(update).rpc();
+    await program.methods.updateWalletLimit(new anchor.BN(0)).accountsPartial(update).rpc();
+  });
+
+  it("Freezes editions on mint until the unlock time", async () => {
+    const update = { config: configPda, authority };
+    const tokenState = async (tokenAccount: PublicKey) =>
+      (await provider.connection.getAccountInfo(tokenAccount))!.data[108];
+    const now = Math.floor(Date.now() / 1000);
+    await program.methods
+      .updateMintLock(true, new anchor.BN(now + 3600))
+      .accountsPartial(update)
+      .rpc();
+
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const accounts = mintAccounts(minter.publicKey, editionMint.publicKey);
+    await program.methods
+      .mintEdition()
+      .accountsPartial(accounts)
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+    expect(await tokenState(accounts.editionTokenAccount)).to.equal(2);
+
+    const unfreeze = () =>
+      program.methods
+        .unfreeze()
+        .accountsPartial({
+          config: configPda,
+          owner: minter.publicKey,
+          editionMint: editionMint.publicKey,
+          editionTokenAccount: accounts.editionTokenAccount,
+          edition: accounts.edition,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([minter])
+        .rpc();
+    await expectError(unfreeze(), "MintLocked");
+
+    await program.methods
+      .updateMintLock(false, new anchor.BN(now - 1))
+      .accountsPartial(update)
+      .rpc();
+    await unfreeze();
+    expect(await tokenState(accounts.editionTokenAccount)).to.equal(1);
+
+    await program.methods.updateMintLock(false, new anchor.BN(0)).accountsPartial(update).rpc();
+  });
+
+  it("Emits a Milestone event once when the count crosses a milestone", async () => {
+    const update = { config: configPda, authority };
+    const { totalMinted } = await program.account.config.fetch(configPda);
+    const milestone = totalMinted.addn(2);
+    await program.methods.updateMilestones(new anchor.BN(0), [milestone]).accountsPartial(update).rpc();
+
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    const milestonesSeen: number[] = [];
+    for (let i = 1; i <= 3; i++) {
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      const signature = await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc({ commitment: "confirmed" });
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      for (const event of parser.parseLogs(tx!.meta!.logMessages!)) {
+        if (event.name === "milestone") milestonesSeen.push(event.data.totalMinted.toNumber());
+      }
+    }
+    expect(milestonesSeen).to.deep.equal([milestone.toNumber()]);
+
+    await program.methods.updateMilestones(new anchor.BN(0), []).accountsPartial(update).rpc();
+  });
+
+  it("Lets the authority thaw and refreeze a delegated edition", async () => {
+    const update = { config: configPda, authority };
+    const tokenState = async (tokenAccount: PublicKey) =>
+      (await provider.connection.getAccountInfo(tokenAccount))!.data[108];
+    await program.methods
+      .updateMintLock(true, new anchor.BN(Math.floor(Date.now() / 1000) + 3600))
+      .accountsPartial(update)
+      .rpc();
+
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const accounts = mintAccounts(minter.publicKey, editionMint.publicKey);
+    await program.methods
+      .mintEdition()
+      .accountsPartial(accounts)
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+    await program.methods.updateMintLock(false, new anchor.BN(0)).accountsPartial(update).rpc();
+
+    const moderated = {
+      config: configPda,
+      authority,
+      editionMint: editionMint.publicKey,
+      editionTokenAccount: accounts.editionTokenAccount,
+      edition: accounts.edition,
+      tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    };
+    const [moderation] = PublicKey.findProgramAddressSync(
+      [Buffer.from("moderation"), editionMint.publicKey.toBuffer()],
+      program.programId
+    );
+    // Frozen on mint, so there is no moderation hold to lift yet
+    await program.methods.adminThaw().accountsPartial({ ...moderated, moderation: null }).rpc();
+    expect(await tokenState(accounts.editionTokenAccount)).to.equal(1);
+    await program.methods
+      .adminFreeze()
+      .accountsPartial({ ...moderated, moderation, systemProgram: SystemProgram.programId })
+      .rpc();
+    expect(await tokenState(accounts.editionTokenAccount)).to.equal(2);
+
+    // The holder cannot lift a moderation freeze, even with the mint lock over
+    await expectError(
+      program.methods
+        .unfreeze()
+        .accountsPartial({
+          config: configPda,
+          owner: minter.publicKey,
+          editionMint: editionMint.publicKey,
+          editionTokenAccount: accounts.editionTokenAccount,
+          edition: accounts.edition,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([minter])
+        .rpc(),
+      "EditionModerated"
+    );
+
+    const outsider = await fundedWallet(1);
+    await expectError(
+      program.methods
+        .adminThaw()
+        .accountsPartial({ ...moderated, moderation, authority: outsider.publicKey })
+        .signers([outsider])
+        .rpc(),
+      "ConstraintHasOne"
+    );
+    await program.methods.adminThaw().accountsPartial({ ...moderated, moderation }).rpc();
+    expect(await provider.connection.getAccountInfo(moderation)).to.be.null;
+
+    // Moderation only reaches delegated editions; a regular mint is out of scope
+    const regularMint = Keypair.generate();
+    const regular = mintAccounts(minter.publicKey, regularMint.publicKey);
+    await program.methods
+      .mintEdition()
+      .accountsPartial(regular)
+      .preInstructions(computeBudget)
+      .signers([minter, regularMint])
+      .rpc();
+    const [regularModeration] = PublicKey.findProgramAddressSync(
+      [Buffer.from("moderation"), regularMint.publicKey.toBuffer()],
+      program.programId
+    );
+    await expectError(
+      program.methods
+        .adminFreeze()
+        .accountsPartial({
+          ...moderated,
+          editionMint: regularMint.publicKey,
+          editionTokenAccount: regular.editionTokenAccount,
+          edition: regular.edition,
+          moderation: regularModeration,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      "EditionNotModeratable"
+    );
+  });
+
+  it("Mints three editions in one batch paid up front", async () => {
+    const minter = await fundedWallet(5);
+    const editionMints = [0, 1, 2].map(() => Keypair.generate());
+    const editionAccounts = (editionMint: PublicKey) => [
+      { pubkey: editionMint, isSigner: true, isWritable: true },
+      {
+        pubkey: anchor.utils.token.associatedAddress({ mint: editionMint, owner: minter.publicKey }),
+        isSigner: false,
+        isWritable: true,
+      },
+      { pubkey: metadataPda(editionMint), isSigner: false, isWritable: true },
+      { pubkey: editionPda(editionMint), isSigner: false, isWritable: true },
+    ];
+    const mintBatch = (quantity: number, mints: Keypair[]) =>
+      program.methods
+        .mintBatch(quantity)
+        .accountsPartial({
+          config: configPda,
+          minter: minter.publicKey,
+          paymentVault,
+          minterRecord: minterRecordPda(minter.publicKey),
+          priceFeed: null,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+          holderTokenAccount: null,
+          holderMetadata: null,
+        })
+        .remainingAccounts(mints.flatMap((mint) => editionAccounts(mint.publicKey)))
+        .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 1_200_000 })])
+        .signers([minter, ...mints])
+        .rpc();
+
+    await expectError(mintBatch(3, editionMints.slice(0, 2)), "InvalidMintBatchAccounts");
+
+    const { mintPrice, totalMinted } = await program.account.config.fetch(configPda);
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    await mintBatch(3, editionMints);
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(
+      vaultBefore + mintPrice.toNumber() * 3
+    );
+
+    for (const [i, editionMint] of editionMints.entries()) {
+      const balance = await provider.connection.getTokenAccountBalance(
+        anchor.utils.token.associatedAddress({ mint: editionMint.publicKey, owner: minter.publicKey })
+      );
+      expect(balance.value.uiAmount).to.equal(1);
+      expect((await fetchMetadata(editionMint.publicKey)).name).to.equal(
+        `${editionName} #${totalMinted.toNumber() + i + 1}`
+      );
+    }
+    const record = await program.account.minterRecord.fetch(minterRecordPda(minter.publicKey));
+    expect(record.count.toNumber()).to.equal(3);
+  });
+
+  it("Discounts a batch that reaches a bulk tier", async () => {
+    const update = { config: configPda, authority };
+    await program.methods
+      .updateBulkTiers([
+        { minQuantity: new anchor.BN(3), discountBps: 500 },
+        { minQuantity: new anchor.BN(5), discountBps: 1_000 },
+      ])
+      .accountsPartial(update)
+      .rpc();
+
+    const minter = await fundedWallet(5);
+    const mintBatch = async (quantity: number) => {
+      const mints = Array.from({ length: quantity }, () => Keypair.generate());
+      const vaultBefore = await provider.connection.getBalance(paymentVault);
+      await program.methods
+        .mintBatch(quantity)
+        .accountsPartial({
+          config: configPda,
+          minter: minter.publicKey,
+          paymentVault,
+          minterRecord: minterRecordPda(minter.publicKey),
+          priceFeed: null,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: SystemProgram.programId,
+          rent: SYSVAR_RENT_PUBKEY,
+          holderTokenAccount: null,
+          holderMetadata: null,
+        })
+        .remainingAccounts(
+          mints.flatMap(({ publicKey }) => [
+            { pubkey: publicKey, isSigner: true, isWritable: true },
+            {
+              pubkey: anchor.utils.token.associatedAddress({ mint: publicKey, owner: minter.publicKey }),
+              isSigner: false,
+              isWritable: true,
+            },
+            { pubkey: metadataPda(publicKey), isSigner: false, isWritable: true },
+            { pubkey: editionPda(publicKey), isSigner: false, isWritable: true },
+          ])
+        )
+        .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 1_200_000 })])
+        .signers([minter, ...mints])
+        .rpc();
+      return (await provider.connection.getBalance(paymentVault)) - vaultBefore;
+    };
+
+    const { mintPrice } = await program.account.config.fetch(configPda);
+    // Below the first tier the batch pays full price, at 3+ it gets 5% off
+    expect(await mintBatch(2)).to.equal(mintPrice.toNumber() * 2);
+    expect(await mintBatch(3)).to.equal((mintPrice.toNumber() * 3 * 95) / 100);
+
+    await expectError(
+      program.methods
+        .updateBulkTiers([{ minQuantity: new anchor.BN(1), discountBps: 500 }])
+        .accountsPartial(update)
+        .rpc(),
+      "InvalidBulkTiers"
+    );
+    await program.methods.updateBulkTiers([]).accountsPartial(update).rpc();
+  });
+
+  it("Reveals an edition only with its claim code", async () => {
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const accounts = mintAccounts(minter.publicKey, editionMint.publicKey);
+    const [receipt] = PublicKey.findProgramAddressSync(
+      [Buffer.from("receipt"), editionMint.publicKey.toBuffer()],
+      program.programId
+    );
+    await program.methods
+      .mintWithMetadataHash(Array(32).fill(0))
+      .accountsPartial({ mint: accounts, authority, receipt, systemProgram: SystemProgram.programId })
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+    await program.methods
+      .claimEditionAuthority()
+      .accountsPartial({
+        config: configPda,
+        editionMint: editionMint.publicKey,
+        editionMetadata: accounts.editionMetadata,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        updateAuthority: minter.publicKey,
+      })
+      .signers([minter])
+      .rpc();
+
+    const baseUri = "https://example.com/revealed/";
+    const secret = Buffer.from("founder-claim-code");
+    await program.methods
+      .updateRevealBaseUri(baseUri)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await program.methods
+      .setRevealHash(Array.from(createHash("sha256").update(secret).digest()))
+      .accountsPartial({ config: configPda, authority, receipt })
+      .rpc();
+
+    const reveal = (code: Buffer) =>
+      program.methods
+        .revealWithSecret(code)
+        .accountsPartial({
+          config: configPda,
+          holder: minter.publicKey,
+          receipt,
+          editionMint: editionMint.publicKey,
+          holderTokenAccount: accounts.editionTokenAccount,
+          editionMetadata: accounts.editionMetadata,
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        })
+        .signers([minter])
+        .rpc();
+    await expectError(reveal(Buffer.from("wrong-code")), "InvalidRevealSecret");
+    expect((await fetchMetadata(editionMint.publicKey)).uri).to.equal(editionUri);
+
+    await reveal(secret);
+    const { editionNumber } = await program.account.mintReceipt.fetch(receipt);
+    expect((await fetchMetadata(editionMint.publicKey)).uri).to.equal(
+      `${baseUri}${editionNumber.toNumber()}.json`
+    );
+    await expectError(reveal(secret), "InvalidRevealSecret");
+
+    await program.methods
+      .updateRevealBaseUri("")
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Returns per-wallet mint counts, 0 for wallets that never minted", async () => {
+    const minter = await fundedWallet();
+    for (let i = 0; i < 2; i++) {
+      const editionMint = Keypair.generate();
+      await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    }
+    const count = (wallet: PublicKey) =>
+      program.methods
+        .getMinterCount(wallet)
+        .accountsPartial({ minterRecord: minterRecordPda(wallet) })
+        .view();
+
+    expect((await count(minter.publicKey)).toNumber()).to.equal(2);
+    expect((await count(Keypair.generate().publicKey)).toNumber()).to.equal(0);
+  });
+
+  it("Returns a minter's history receipt, zeroed for wallets that never minted", async () => {
+    const minter = await fundedWallet();
+    const mint = async (method: "mintEdition" | "mintDiscounted") => {
+      const editionMint = Keypair.generate();
+      const builder =
+        method === "mintEdition" ? program.methods.mintEdition() : program.methods.mintDiscounted([]);
+      await builder
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+    const receipt = (wallet: PublicKey) =>
+      program.methods
+        .getMinterReceipt(wallet)
+        .accountsPartial({ minterRecord: minterRecordPda(wallet) })
+        .view();
+
+    const before = Math.floor(Date.now() / 1000) - 60;
+    await mint("mintEdition");
+    await mint("mintDiscounted");
+    const summary = await receipt(minter.publicKey);
+    expect(summary.minter.toBase58()).to.equal(minter.publicKey.toBase58());
+    expect(summary.count.toNumber()).to.equal(2);
+    expect(summary.discountedCount.toNumber()).to.equal(1);
+    expect(summary.lifetimeCount.toNumber()).to.equal(2);
+    expect(summary.lastMintTs.toNumber()).to.be.greaterThan(before);
+
+    const empty = await receipt(Keypair.generate().publicKey);
+    expect(empty.count.toNumber()).to.equal(0);
+    expect(empty.discountedCount.toNumber()).to.equal(0);
+    expect(empty.lastMintTs.toNumber()).to.equal(0);
+    expect(empty.lifetimeCount.toNumber()).to.equal(0);
+  });
+
+  it("Throttles each wallet to its daily mint limit", async () => {
+    await program.methods
+      .updateDailyLimit(new anchor.BN(1))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    const mint = async (minter: Keypair) => {
+      const editionMint = Keypair.generate();
+      await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+
+    // The local validator cannot warp a day, so the reset is covered by a unit test
+    const minter = await fundedWallet();
+    await mint(minter);
+    await expectError(mint(minter), "DailyLimitReached");
+    await mint(await fundedWallet());
+    const record = await program.account.minterRecord.fetch(minterRecordPda(minter.publicKey));
+    expect(record.dailyCount.toNumber()).to.equal(1);
+    expect(record.day.toNumber()).to.equal(Math.floor(record.lastMintTs.toNumber() / 86_400));
+
+    await program.methods
+      .updateDailyLimit(new anchor.BN(0))
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+    await mint(minter);
+  });
+
+  it("Reports every reason a wallet cannot mint at once", async () => {
+    const update = { config: configPda, authority };
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+    const eligibility = (wallet: PublicKey, record: PublicKey | null) =>
+      program.methods
+        .eligibility([])
+        .accountsPartial({
+          config: configPda,
+          minter: wallet,
+          minterRecord: record,
+          holderTokenAccount: null,
+          holderMetadata: null,
+        })
+        .view();
+    expect(await eligibility(minter.publicKey, minterRecordPda(minter.publicKey))).to.equal(0);
+
+    await program.methods.setPaused(true).accountsPartial(update).rpc();
+    await program.methods.setAllowlistRoot(Array(32).fill(7)).accountsPartial(update).rpc();
+    await program.methods.updateWalletLimit(new anchor.BN(1)).accountsPartial(update).rpc();
+    await program.methods.updateDailyLimit(new anchor.BN(1)).accountsPartial(update).rpc();
+
+    // Paused (1), not allowlisted (16), wallet limit (32) and daily cooldown (64)
+    expect(await eligibility(minter.publicKey, minterRecordPda(minter.publicKey))).to.equal(
+      1 | 16 | 32 | 64
+    );
+    // A wallet that never minted only trips the drop-wide checks
+    expect(await eligibility(Keypair.generate().publicKey, null)).to.equal(1 | 16);
+
+    await program.methods.setPaused(false).accountsPartial(update).rpc();
+    await program.methods.setAllowlistRoot(Array(32).fill(0)).accountsPartial(update).rpc();
+    await program.methods.updateWalletLimit(new anchor.BN(0)).accountsPartial(update).rpc();
+    await program.methods.updateDailyLimit(new anchor.BN(0)).accountsPartial(update).rpc();
+    expect(await eligibility(minter.publicKey, minterRecordPda(minter.publicKey))).to.equal(0);
+  });
+
+  it("Reads several minter records in one call", async () => {
+    const minters = [await fundedWallet(), await fundedWallet(), await fundedWallet()];
+    for (const [i, minter] of minters.entries()) {
+      for (let n = 0; n <= i; n++) {
+        const editionMint = Keypair.generate();
+        await program.methods
+          .mintEdition()
+          .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+          .preInstructions(computeBudget)
+          .signers([minter, editionMint])
+          .rpc();
+      }
+    }
+    const wallets = [...minters.map((minter) => minter.publicKey), Keypair.generate().publicKey];
+    const remaining = (pairs: [PublicKey, PublicKey][]) =>
+      pairs.flatMap(([wallet, record]) => [
+        { pubkey: wallet, isSigner: false, isWritable: false },
+        { pubkey: record, isSigner: false, isWritable: false },
+      ]);
+
+    const summaries = await program.methods
+      .getMinterRecords()
+      .accountsPartial({ config: configPda })
+      .remainingAccounts(remaining(wallets.map((wallet) => [wallet, minterRecordPda(wallet)])))
+      .view();
+    expect(summaries.map((summary) => summary.minter.toBase58())).to.deep.equal(
+      wallets.map((wallet) => wallet.toBase58())
+    );
+    expect(summaries.map((summary) => summary.count.toNumber())).to.deep.equal([1, 2, 3, 0]);
+
+    // A record must be the PDA of the wallet it follows
+    await expectError(
+      program.methods
+        .getMinterRecords()
+        .accountsPartial({ config: configPda })
+        .remainingAccounts(remaining([[wallets[0], minterRecordPda(wallets[1])]]))
+        .view(),
+      "InvalidMinterRecordAccounts"
+    );
+  });
+
+  it("Mints by burning a redeemable voucher token", async () => {
+    const voucherMintKeypair = Keypair.generate();
+    const voucherMint = voucherMintKeypair.publicKey;
+    const minter = await fundedWallet();
+    const voucherAccount = anchor.utils.token.associatedAddress({
+      mint: voucherMint,
+      owner: minter.publicKey,
+    });
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        // One and a half vouchers at two decimals
+        ...(await createMintIxs(voucherMint, 2)),
+        createAtaIx(voucherMint, minter.publicKey),
+        mintToIx(voucherMint, voucherAccount, 150)
+      ),
+      [voucherMintKeypair]
+    );
+    await program.methods
+      .updateRedeemableMint(voucherMint)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+
+    const mintByBurning = () => {
+      const editionMint = Keypair.generate();
+      return program.methods
+        .mintByBurning()
+        .accountsPartial({
+          mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+          voucherMint,
+          voucherTokenAccount: voucherAccount,
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    };
+
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    await mintByBurning();
+    expect(await provider.connection.getBalance(paymentVault)).to.equal(vaultBefore);
+    // Each edition burns one whole voucher
+    const vouchers = await provider.connection.getTokenAccountBalance(voucherAccount);
+    expect(vouchers.value.amount).to.equal("50");
+
+    await expectError(mintByBurning(), "InvalidVoucher");
+
+    await program.methods
+      .updateRedeemableMint(null)
+      .accountsPartial({ config: configPda, authority })
+      .rpc();
+  });
+
+  it("Raises the price along the bonding curve with each edition", async () => {
+    const update = { config: configPda, authority };
+    const basePrice = 50_000_000;
+    const priceIncrement = 1_000_000;
+    await program.methods
+      .updateBondingCurve(true, new anchor.BN(basePrice), new anchor.BN(priceIncrement))
+      .accountsPartial(update)
+      .rpc();
+
+    const paid: number[] = [];
+    for (let i = 0; i < 3; i++) {
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      const { totalMinted } = await program.account.config.fetch(configPda);
+      const vaultBefore = await provider.connection.getBalance(paymentVault);
+      await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+      const price = (await provider.connection.getBalance(paymentVault)) - vaultBefore;
+      expect(price).to.equal(basePrice + priceIncrement * totalMinted.toNumber());
+      paid.push(price);
+    }
+    expect(paid[1] - paid[0]).to.equal(priceIncrement);
+    expect(paid[2] - paid[1]).to.equal(priceIncrement);
+
+    // Re-applying the same curve moves no price, so it announces nothing
+    const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+    const pricingEvents = async (signature: string) => {
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      return [...parser.parseLogs(tx!.meta!.logMessages!)].filter(
+        (event) => event.name === "pricingUpdated"
+      );
+    };
+    const unchanged = await program.methods
+      .updateBondingCurve(true, new anchor.BN(basePrice), new anchor.BN(priceIncrement))
+      .accountsPartial(update)
+      .rpc({ commitment: "confirmed" });
+    expect(await pricingEvents(unchanged)).to.have.length(0);
+
+    const disabled = await program.methods
+      .updateBondingCurve(false, new anchor.BN(0), new anchor.BN(0))
+      .accountsPartial(update)
+      .rpc({ commitment: "confirmed" });
+    expect(await pricingEvents(disabled)).to.have.length(1);
+  });
+
+  it("Scales a ratio discount with the bonding curve price", async () => {
+    const update = { config: configPda, authority };
+    const basePrice = 60_000_000;
+    const priceIncrement = 2_000_000;
+    await program.methods
+      .updateBondingCurve(true, new anchor.BN(basePrice), new anchor.BN(priceIncrement))
+      .accountsPartial(update)
+      .rpc();
+    await program.methods
+      .updateDiscountMode({ ratioOfRegular: {} }, 5_000)
+      .accountsPartial(update)
+      .rpc();
+
+    const paid: number[] = [];
+    for (let i = 0; i < 2; i++) {
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      const { totalMinted } = await program.account.config.fetch(configPda);
+      const vaultBefore = await provider.connection.getBalance(paymentVault);
+      await program.methods
+        .mintDiscounted([])
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+      const price = (await provider.connection.getBalance(paymentVault)) - vaultBefore;
+      expect(price).to.equal((basePrice + priceIncrement * totalMinted.toNumber()) / 2);
+      paid.push(price);
+    }
+    expect(paid[1] - paid[0]).to.equal(priceIncrement / 2);
+
+    await program.methods
+      .updateDiscountMode({ fixed: {} }, 0)
+      .accountsPartial(update)
+      .rpc();
+    await program.methods
+      .updateBondingCurve(false, new anchor.BN(0), new anchor.BN(0))
+      .accountsPartial(update)
+      .rpc();
+  });
+
+  it("Burns an edition down to zero supply and counts it as burned", async () => {
+    const burnable = async () => {
+      const owner = await fundedWallet();
+      const editionMint = Keypair.generate();
+      const accounts = mintAccounts(owner.publicKey, editionMint.publicKey);
+      await program.methods
+        .mintEdition()
+        .accountsPartial(accounts)
+        .preInstructions(computeBudget)
+        .signers([owner, editionMint])
+        .rpc();
+      await verifyEdition(editionMint.publicKey);
+      return {
+        owner,
+        burn: {
+          config: configPda,
+          owner: owner.publicKey,
+          editionMint: editionMint.publicKey,
+          editionTokenAccount: accounts.editionTokenAccount,
+          editionMetadata: accounts.editionMetadata,
+          edition: accounts.edition,
+          // BurnNft updates the size of a verified member's collection
+          collectionMetadata: metadataPda(masterMint),
+          tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        },
+      };
+    };
+
+    const { owner, burn } = await burnable();
+    const before = await program.account.config.fetch(configPda);
+    const stranger = await fundedWallet(1);
+    await expectError(
+      program.methods
+        .burnEdition(false)
+        .accountsPartial({ ...burn, owner: stranger.publicKey })
+        .signers([stranger])
+        .rpc(),
+      "ConstraintTokenOwner"
+    );
+    await program.methods.burnEdition(false).accountsPartial(burn).signers([owner]).rpc();
+
+    const supply = await provider.connection.getTokenSupply(burn.editionMint);
+    expect(supply.value.amount).to.equal("0");
+    expect(await provider.connection.getAccountInfo(burn.editionTokenAccount)).to.be.null;
+    const after = await program.account.config.fetch(configPda);
+    expect(after.totalBurned.toNumber()).to.equal(before.totalBurned.toNumber() + 1);
+    expect(after.totalMinted.toNumber()).to.equal(before.totalMinted.toNumber());
+
+    // BurnNft also closes the metadata and master edition
+    const full = await burnable();
+    await program.methods
+      .burnEdition(true)
+      .accountsPartial(full.burn)
+      .preInstructions(computeBudget)
+      .signers([full.owner])
+      .rpc();
+    expect(await provider.connection.getAccountInfo(full.burn.editionMetadata)).to.be.null;
+    expect(await provider.connection.getAccountInfo(full.burn.edition)).to.be.null;
+    expect(await provider.connection.getAccountInfo(full.burn.editionTokenAccount)).to.be.null;
+  });
+
+  it("Gives the royalty split wallet the full creator share", async () => {
+    const update = { config: configPda, authority };
+    // The wallet must be a live account, unlike a mistyped or unused address
+    await expectError(
+      program.methods
+        .updateRoyaltySplit()
+        .accountsPartial({ ...update, royaltySplitWallet: Keypair.generate().publicKey })
+        .rpc(),
+      "InvalidRoyaltySplit"
+    );
+    const splitWallet = (await fundedWallet(1)).publicKey;
+    await program.methods
+      .updateRoyaltySplit()
+      .accountsPartial({ ...update, royaltySplitWallet: splitWallet })
+      .rpc();
+
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+    await program.methods
+      .updateRoyaltySplit()
+      .accountsPartial({ ...update, royaltySplitWallet: null })
+      .rpc();
+
+    const metadata = await fetchMetadata(editionMint.publicKey);
+    expect(
+      metadata.creators.map((creator) => ({
+        address: creator.address.toBase58(),
+        verified: creator.verified,
+        share: creator.share,
+      }))
+    ).to.deep.equal([
+      { address: splitWallet.toBase58(), verified: false, share: 100 },
+      { address: authority.toBase58(), verified: false, share: 0 },
+      { address: minter.publicKey.toBase58(), verified: true, share: 0 },
+    ]);
+  });
+
+  it("Makes the custodian a full delegate that the holder can revoke", async () => {
+    const custodian = await fundedWallet();
+    const update = { config: configPda, authority };
+    await program.methods
+      .updateCustodian(custodian.publicKey)
+      .accountsPartial(update)
+      .rpc();
+
+    const mintDelegated = async (minter: Keypair) => {
+      const editionMint = Keypair.generate();
+      const accounts = mintAccounts(minter.publicKey, editionMint.publicKey);
+      await program.methods
+        .mintEdition()
+        .accountsPartial({ ...accounts, custodian: custodian.publicKey })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+      return { editionMint: editionMint.publicKey, tokenAccount: accounts.editionTokenAccount };
+    };
+    const minter = await fundedWallet();
+
+    // The delegate can move the edition without the holder's signature
+    const moved = await mintDelegated(minter);
+    const custodianAccount = anchor.utils.token.associatedAddress({
+      mint: moved.editionMint,
+      owner: custodian.publicKey,
+    });
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        createAtaIx(moved.editionMint, custodian.publicKey),
+        new TransactionInstruction({
+          programId: TOKEN_PROGRAM_ID,
+          keys: [
+            key(moved.tokenAccount, false, true),
+            key(custodianAccount, false, true),
+            key(custodian.publicKey, true),
+          ],
+          data: Buffer.concat([Buffer.from([3]), u64(1)]),
+        })
+      ),
+      [custodian]
+    );
+    const custodianBalance = await provider.connection.getTokenAccountBalance(custodianAccount);
+    expect(custodianBalance.value.amount).to.equal("1");
+
+    // SPL Revoke by the holder clears the delegation
+    const revoked = await mintDelegated(minter);
+    await provider.sendAndConfirm(
+      new Transaction().add(
+        new TransactionInstruction({
+          programId: TOKEN_PROGRAM_ID,
+          keys: [key(revoked.tokenAccount, false, true), key(minter.publicKey, true)],
+          data: Buffer.from([5]),
+        })
+      ),
+      [minter]
+    );
+    const token = (await provider.connection.getAccountInfo(revoked.tokenAccount))!.data;
+    expect(token.readUInt32LE(72)).to.equal(0);
+
+    await program.methods.updateCustodian(null).accountsPartial(update).rpc();
+  });
+
+  it("Applies the price cooldown and grace period to curve and USD changes", async () => {
+    const update = { config: configPda, authority };
+    const { mintPrice } = await program.account.config.fetch(configPda);
+    const disableCurve = () =>
+      program.methods
+        .updateBondingCurve(false, new anchor.BN(0), new anchor.BN(0))
+        .accountsPartial(update)
+        .rpc();
+
+    // Moving onto a pricier curve keeps charging the flat price through the grace period
+    await program.methods.updatePriceGrace(new anchor.BN(600)).accountsPartial(update).rpc();
+    await program.methods
+      .updateBondingCurve(true, mintPrice.muln(2), new anchor.BN(1_000_000))
+      .accountsPartial(update)
+      .rpc();
+    const minter = await fundedWallet();
+    const editionMint = Keypair.generate();
+    const vaultBefore = await provider.connection.getBalance(paymentVault);
+    await program.methods
+      .mintEdition()
+      .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+      .preInstructions(computeBudget)
+      .signers([minter, editionMint])
+      .rpc();
+    expect((await provider.connection.getBalance(paymentVault)) - vaultBefore).to.equal(
+      mintPrice.toNumber()
+    );
+    await disableCurve();
+    await program.methods.updatePriceGrace(new anchor.BN(0)).accountsPartial(update).rpc();
+
+    // Curve and USD changes count against the same cooldown as flat price changes
+    await program.methods.updatePriceCooldown(new anchor.BN(3600)).accountsPartial(update).rpc();
+    await expectError(disableCurve(), "PriceChangeTooSoon");
+    await expectError(
+      program.methods
+        .updateUsdPricing(new anchor.BN(0), PublicKey.default, new anchor.BN(0), Array(32).fill(0), 0)
+        .accountsPartial(update)
+        .rpc(),
+      "PriceChangeTooSoon"
+    );
+    await program.methods.updatePriceCooldown(new anchor.BN(0)).accountsPartial(update).rpc();
+  });
+
+  it("Refuses to migrate a config that already has the current layout", async () => {
+    await expectError(
+      program.methods
+        .migrateConfig()
+        .accountsPartial({
+          config: configPda,
+          authority,
+          masterMetadata: metadataPda(masterMint),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc(),
+      "ConfigAlreadyMigrated"
+    );
+  });
+
+  // Child suites run after every test above, so terminal config states live here
+  describe("closed config", () => {
+    it("Closes the config only when paused with an empty vault", async () => {
+      const closeAccounts = { config: configPda, authority, paymentVault, tokenVault };
+      await expectError(
+        program.methods.closeConfig().accountsPartial(closeAccounts).rpc(),
+        "NotPaused"
+      );
+      await program.methods
+        .setPaused(true)
+        .accountsPartial({ config: configPda, authority })
+        .rpc();
+      await expectError(
+        program.methods.closeConfig().accountsPartial(closeAccounts).rpc(),
+        "VaultNotEmpty"
+      );
+
+      const vaultBalance = await provider.connection.getBalance(paymentVault);
+      await program.methods
+        .withdraw(new anchor.BN(vaultBalance))
+        .accountsPartial({ config: configPda, authority, paymentVault })
+        .rpc();
+
+      // Unwithdrawn SPL payments also keep the config open
+      await provider.sendAndConfirm(new Transaction().add(mintToIx(paymentMint, tokenVault, 1)));
+      await expectError(
+        program.methods.closeConfig().accountsPartial(closeAccounts).rpc(),
+        "VaultNotEmpty"
+      );
+      await program.methods
+        .withdrawTokens(new anchor.BN(1))
+        .accountsPartial({
+          config: configPda,
+          authority,
+          tokenVault,
+          destination: anchor.utils.token.associatedAddress({ mint: paymentMint, owner: authority }),
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+
+      const configRent = await provider.connection.getBalance(configPda);
+      const before = await provider.connection.getBalance(authority);
+      const signature = await program.methods
+        .closeConfig()
+        .accountsPartial(closeAccounts)
+        .rpc({ commitment: "confirmed" });
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      expect(await provider.connection.getAccountInfo(configPda)).to.equal(null);
+      expect((await provider.connection.getBalance(authority)) - before + tx!.meta!.fee).to.equal(
+        configRent
+      );
+
+      // Only the upgrade authority may start the fresh config for the suites below
+      await expectError(initializeConfig({ signer: await fundedWallet(1) }), "Unauthorized");
+      // Royalties must go to the collection's primary creator from the start
+      await expectError(
+        initializeConfig({ royaltyRecipient: Keypair.generate().publicKey }),
+        "MissingCollectionCreator"
+      );
+      await initializeConfig();
+    });
+
+    it("Caps the supply at a sized collection's size", async () => {
+      // Turn a fresh collection into a sized one of 3 through SetCollectionSize
+      const sizedCollection = Keypair.generate();
+      await createCollectionNft(sizedCollection);
+      await provider.sendAndConfirm(
+        new Transaction().add(
+          new TransactionInstruction({
+            programId: TOKEN_METADATA_PROGRAM_ID,
+            keys: [
+              key(metadataPda(sizedCollection.publicKey), false, true),
+              key(authority, true, true),
+              key(sizedCollection.publicKey),
+            ],
+            data: Buffer.concat([Buffer.from([34]), u64(3)]),
+          })
+        )
+      );
+
+      const update = { config: configPda, authority };
+      const closeActiveConfig = async () => {
+        await program.methods.setPaused(true).accountsPartial(update).rpc();
+        await program.methods
+          .closeConfig()
+          .accountsPartial({ config: configPda, authority, paymentVault, tokenVault })
+          .rpc();
+      };
+      await closeActiveConfig();
+
+      await expectError(
+        initializeConfig({ collection: sizedCollection.publicKey, maxSupply: 4 }),
+        "SupplyExceedsCollection"
+      );
+      await initializeConfig({ collection: sizedCollection.publicKey, maxSupply: 3 });
+      expect((await program.account.config.fetch(configPda)).maxSupply.toNumber()).to.equal(3);
+      // Later cap changes stay within the collection size too
+      await expectError(
+        program.methods
+          .updateMaxSupply(new anchor.BN(4))
+          .accountsPartial({ ...update, masterMetadata: metadataPda(sizedCollection.publicKey) })
+          .rpc(),
+        "SupplyExceedsCollection"
+      );
+      await closeActiveConfig();
+      await initializeConfig({ collection: sizedCollection.publicKey });
+      expect((await program.account.config.fetch(configPda)).maxSupply.toNumber()).to.equal(3);
+      await closeActiveConfig();
+
+      await initializeConfig();
+    });
+
+    it("Mints a chosen edition number from the reserved range only once", async () => {
+      const update = { config: configPda, authority };
+      await program.methods.setPaused(true).accountsPartial(update).rpc();
+      await program.methods
+        .closeConfig()
+        .accountsPartial({ config: configPda, authority, paymentVault, tokenVault })
+        .rpc();
+      await initializeConfig({ maxSupply: 3 });
+      // Edition #3 is reserved, sequential mints stop at #2
+      await program.methods.updateReservedEditions(new anchor.BN(1)).accountsPartial(update).rpc();
+
+      const mintAt = (editionNumber: number, editionMint: Keypair) =>
+        fundedWallet().then((minter) =>
+          program.methods
+            .mintEditionAt(new anchor.BN(editionNumber))
+            .accountsPartial({
+              mint: mintAccounts(minter.publicKey, editionMint.publicKey),
+              editionIndex: PublicKey.findProgramAddressSync(
+                [Buffer.from("edition_index"), new anchor.BN(editionNumber).toArrayLike(Buffer, "le", 8)],
+                program.programId
+              )[0],
+              systemProgram: SystemProgram.programId,
+            })
+            .preInstructions(computeBudget)
+            .signers([minter, editionMint])
+            .rpc()
+        );
+      const mintNext = async () => {
+        const minter = await fundedWallet();
+        const editionMint = Keypair.generate();
+        await program.methods
+          .mintEdition()
+          .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+          .preInstructions(computeBudget)
+          .signers([minter, editionMint])
+          .rpc();
+        return editionMint.publicKey;
+      };
+
+      await expectError(mintAt(2, Keypair.generate()), "InvalidEditionNumber");
+      await expectError(mintAt(4, Keypair.generate()), "InvalidEditionNumber");
+      const reserved = Keypair.generate();
+      await mintAt(3, reserved);
+      expect((await fetchMetadata(reserved.publicKey)).name).to.equal(`${editionName} #3`);
+      await expectError(mintAt(3, Keypair.generate()), "EditionTaken");
+      // The range is fixed once a number is claimed
+      await expectError(
+        program.methods.updateReservedEditions(new anchor.BN(0)).accountsPartial(update).rpc(),
+        "InvalidReservedEditions"
+      );
+
+      expect((await fetchMetadata(await mintNext())).name).to.equal(`${editionName} #1`);
+      expect((await fetchMetadata(await mintNext())).name).to.equal(`${editionName} #2`);
+      // Selling out the last sequential edition pauses the drop
+      expect((await program.account.config.fetch(configPda)).paused).to.equal(true);
+      await program.methods.setPaused(false).accountsPartial(update).rpc();
+      await expectError(mintNext(), "MaxSupplyReached");
+
+      await program.methods.setPaused(true).accountsPartial(update).rpc();
+      const vaultBalance = await provider.connection.getBalance(paymentVault);
+      await program.methods
+        .withdraw(new anchor.BN(vaultBalance))
+        .accountsPartial({ config: configPda, authority, paymentVault })
+        .rpc();
+      await program.methods
+        .closeConfig()
+        .accountsPartial({ config: configPda, authority, paymentVault, tokenVault })
+        .rpc();
+      await initializeConfig();
+    });
+
+    it("Finalizes a sold-out collection once, locking its metadata and supply", async () => {
+      const update = { config: configPda, authority };
+      await program.methods.setPaused(true).accountsPartial(update).rpc();
+      await program.methods
+        .closeConfig()
+        .accountsPartial({ config: configPda, authority, paymentVault, tokenVault })
+        .rpc();
+      const collection = Keypair.generate();
+      await createCollectionNft(collection);
+      await initializeConfig({ collection: collection.publicKey, maxSupply: 1 });
+
+      const finalize = () =>
+        program.methods
+          .finalizeCollection()
+          .accountsPartial({
+            ...update,
+            masterMetadata: metadataPda(collection.publicKey),
+            tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+          })
+          .rpc({ commitment: "confirmed" });
+      await expectError(finalize(), "NotSoldOut");
+
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      await program.methods
+        .mintEdition()
+        .accountsPartial({
+          ...mintAccounts(minter.publicKey, editionMint.publicKey),
+          masterMint: collection.publicKey,
+          masterEdition: editionPda(collection.publicKey),
+          masterMetadata: metadataPda(collection.publicKey),
+        })
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+
+      const signature = await finalize();
+      const parser = new anchor.EventParser(program.programId, new anchor.BorshCoder(program.idl));
+      const tx = await provider.connection.getTransaction(signature, {
+        commitment: "confirmed",
+        maxSupportedTransactionVersion: 0,
+      });
+      const complete = [...parser.parseLogs(tx!.meta!.logMessages!)].find(
+        (event) => event.name === "collectionComplete"
+      );
+      expect(complete!.data.masterMint.toBase58()).to.equal(collection.publicKey.toBase58());
+      expect(complete!.data.totalMinted.toNumber()).to.equal(1);
+      expect(complete!.data.maxSupply.toNumber()).to.equal(1);
+      expect((await fetchMetadata(collection.publicKey)).isMutable).to.equal(false);
+
+      await expectError(finalize(), "CollectionFinalized");
+      await expectError(
+        program.methods
+          .updateMaxSupply(new anchor.BN(2))
+          .accountsPartial({ ...update, masterMetadata: metadataPda(collection.publicKey) })
+          .rpc(),
+        "CollectionFinalized"
+      );
+
+      const vaultBalance = await provider.connection.getBalance(paymentVault);
+      await program.methods
+        .withdraw(new anchor.BN(vaultBalance))
+        .accountsPartial({ config: configPda, authority, paymentVault })
+        .rpc();
+      await program.methods
+        .closeConfig()
+        .accountsPartial({ config: configPda, authority, paymentVault, tokenVault })
+        .rpc();
+      await initializeConfig();
+    });
+  });
+
+  describe("finalized drop", () => {
+    it("Rejects parameter changes after finalizing while mints continue", async () => {
+      await program.methods
+        .finalizeDrop()
+        .accountsPartial({ config: configPda, authority })
+        .rpc();
+
+      const update = { config: configPda, authority };
+      await expectError(
+        program.methods
+          .updatePricing(new anchor.BN(1), null, null)
+          .accountsPartial(update)
+          .rpc(),
+        "ConfigFinalized"
+      );
+      await expectError(
+        program.methods.updateMutability(false, null).accountsPartial(update).rpc(),
+        "ConfigFinalized"
+      );
+      await expectError(
+        program.methods.updateReferralRate(500).accountsPartial(update).rpc(),
+        "ConfigFinalized"
+      );
+      await expectError(
+        program.methods.finalizeDrop().accountsPartial(update).rpc(),
+        "ConfigFinalized"
+      );
+
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+    });
+
+    it("Closes a minter record after finalizing and refunds its rent", async () => {
+      const minter = await fundedWallet();
+      const editionMint = Keypair.generate();
+      await program.methods
+        .mintEdition()
+        .accountsPartial(mintAccounts(minter.publicKey, editionMint.publicKey))
+        .preInstructions(computeBudget)
+        .signers([minter, editionMint])
+        .rpc();
+
+      const minterRecord = minterRecordPda(minter.publicKey);
+      const closeRecord = () =>
+        program.methods
+          .closeMinterRecord()
+          .accountsPartial({ config: configPda, minter: minter.publicKey, minterRecord })
+          .signers([minter])
+          .rpc();
+      // The record still enforces wallet limits while minting continues
+      await expectError(closeRecord(), "MintStillOpen");
+
+      await program.methods
+        .setPaused(true)
+        .accountsPartial({ config: configPda, authority })
+        .rpc();
+      const rent = await provider.connection.getBalance(minterRecord);
+      const before = await provider.connection.getBalance(minter.publicKey);
+      await closeRecord();
+
+      expect(await provider.connection.getAccountInfo(minterRecord)).to.equal(null);
+      expect(await provider.connection.getBalance(minter.publicKey)).to.equal(before + rent);
+    });
+
+    it("Pauses and closes a finalized drop", async () => {
+      await program.methods
+        .setPaused(true)
+        .accountsPartial({ config: configPda, authority })
+        .rpc();
+      const vaultBalance = await provider.connection.getBalance(paymentVault);
+      await program.methods
+        .withdraw(new anchor.BN(vaultBalance))
+        .accountsPartial({ config: configPda, authority, paymentVault })
+        .rpc();
+      await program.methods
+        .closeConfig()
+        .accountsPartial({ config: configPda, authority, paymentVault, tokenVault })
+        .rpc();
+      expect(await provider.connection.getAccountInfo(configPda)).to.equal(null);
+    });
   });
 });